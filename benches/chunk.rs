@@ -0,0 +1,49 @@
+#![feature(allocator_api)]
+#![feature(test)]
+
+extern crate porus;
+extern crate test;
+
+use porus::capacity::DefaultPolicy;
+use porus::chunk::{Index, NonMaxU32, NonMaxUsize};
+use porus::prelude::*;
+use std::alloc::Global;
+use test::{black_box, Bencher};
+
+const N: usize = 100_000;
+
+/// Build a list of `N` nodes, remove every other one and walk the rest.
+fn walk<I: Index>() -> usize {
+    let chunk = Chunk::<usize, DefaultPolicy, Global, I>::new_with_capacity(N);
+    let list = &mut DoublyLinkedList::new_with_pool(chunk);
+    for i in 0..N {
+        list.insert_before(i, None);
+    }
+
+    let mut h = list.front();
+    while let Some(x) = h {
+        h = list.next(Some(x));
+        if let Some(y) = h {
+            h = list.next(Some(y));
+            list.remove(y);
+        }
+    }
+
+    let mut sum = 0;
+    let mut h = list.front();
+    while let Some(x) = h {
+        sum += *list.get(x);
+        h = list.next(Some(x));
+    }
+    sum
+}
+
+#[bench]
+fn bench_list_usize(b: &mut Bencher) {
+    b.iter(|| black_box(walk::<NonMaxUsize>()));
+}
+
+#[bench]
+fn bench_list_u32(b: &mut Bencher) {
+    b.iter(|| black_box(walk::<NonMaxU32>()));
+}
@@ -0,0 +1,348 @@
+//! Expansion of the formatting macros of `porus_macros`, which
+//! cannot be bundled.
+//!
+//! `porus_macros/format.rs` parses format strings with rustc's
+//! internal `fmt_macros` crate, which is only reachable through
+//! `#![feature(rustc_private)]` on the pinned nightly, while this
+//! crate builds on stable. So the parser is duplicated here, covering
+//! only the subset of the syntax which `f!` accepts. Keep the two
+//! in sync.
+
+use proc_macro2::{Delimiter, Group, Literal, TokenStream, TokenTree};
+use syn::parse::{ParseStream, Parser, Result};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{Expr, LitStr};
+
+enum Count {
+    Is(usize),
+    Param(usize),
+    Implied,
+}
+
+struct Argument {
+    position: usize,
+    width: Count,
+    precision: Count,
+    ty: String,
+}
+
+enum Piece {
+    String(String),
+    Argument(Argument),
+}
+
+fn parse_count(s: &str, next: &mut usize) -> std::result::Result<(Count, usize), String> {
+    if s.starts_with('*') {
+        let i = *next;
+        *next += 1;
+        return Ok((Count::Param(i), 1));
+    }
+
+    let digits = s.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return Ok((Count::Implied, 0));
+    }
+
+    let n = s[..digits].parse().map_err(|_| "count overflow")?;
+    if s[digits..].starts_with('$') {
+        Ok((Count::Param(n), digits + 1))
+    } else {
+        Ok((Count::Is(n), digits))
+    }
+}
+
+fn parse_argument(spec: &str, next: &mut usize) -> std::result::Result<Argument, String> {
+    let (pos, fmt) = match spec.find(':') {
+        Some(i) => (&spec[..i], &spec[i + 1..]),
+        None => (spec, ""),
+    };
+
+    let explicit = if pos.is_empty() {
+        None
+    } else {
+        Some(
+            pos.parse()
+                .map_err(|_| format!("named argument not supported: {}", pos))?,
+        )
+    };
+
+    let (width, n) = parse_count(fmt, next)?;
+    let mut rest = &fmt[n..];
+    let precision = if rest.starts_with('.') {
+        let (precision, n) = parse_count(&rest[1..], next)?;
+        rest = &rest[n + 1..];
+        precision
+    } else {
+        Count::Implied
+    };
+
+    let position = explicit.unwrap_or_else(|| {
+        let i = *next;
+        *next += 1;
+        i
+    });
+
+    Ok(Argument {
+        position,
+        width,
+        precision,
+        ty: rest.to_string(),
+    })
+}
+
+fn parse_format(s: &str) -> std::result::Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut next = 0;
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().map(|&(_, c)| c) == Some('{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek().map(|&(_, c)| c) == Some('}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let end = s[i..].find('}').ok_or("unmatched `{` in format string")?;
+                let spec = &s[i + 1..i + end];
+                while chars.peek().map_or(false, |&(j, _)| j <= i + end) {
+                    chars.next();
+                }
+                if !text.is_empty() {
+                    pieces.push(Piece::String(std::mem::take(&mut text)));
+                }
+                pieces.push(Piece::Argument(parse_argument(spec, &mut next)?));
+            }
+            '}' => return Err("unmatched `}` in format string".to_string()),
+            _ => text.push(c),
+        }
+    }
+
+    if !text.is_empty() {
+        pieces.push(Piece::String(text));
+    }
+
+    Ok(pieces)
+}
+
+fn args(input: ParseStream) -> Result<(LitStr, Vec<Expr>)> {
+    let s: LitStr = input.parse()?;
+
+    if !input.is_empty() {
+        let _: Comma = input.parse()?;
+    }
+
+    let args: Punctuated<Expr, Comma> = Punctuated::parse_terminated(input)?;
+    Ok((s, args.into_iter().collect()))
+}
+
+fn count(c: &Count, default: usize) -> TokenStream {
+    match *c {
+        Count::Is(n) => {
+            let lit = Literal::usize_unsuffixed(n);
+            quote! { #lit }
+        }
+        Count::Param(i) => {
+            let lit = Literal::usize_unsuffixed(i);
+            quote! { porus_args.#lit }
+        }
+        Count::Implied => {
+            let lit = Literal::usize_unsuffixed(default);
+            quote! { #lit }
+        }
+    }
+}
+
+/// Same expansion as `porus_macros::format`, with paths qualified
+/// since the result no longer sits inside `porus::fmt`.
+fn format(tokens: TokenStream) -> std::result::Result<TokenStream, String> {
+    let (s, args) = Parser::parse2(args, tokens).map_err(|e| e.to_string())?;
+
+    let mut stream = quote! {};
+    for piece in parse_format(&s.value())? {
+        match piece {
+            Piece::String(s) => {
+                let lit = Literal::string(&s);
+                stream = quote! { #stream crate::porus::fmt::fwrite_str(porus_sink, #lit); };
+            }
+            Piece::Argument(a) => {
+                let pos = Literal::usize_unsuffixed(a.position);
+                let arg = quote! { porus_args.#pos };
+                stream = match a.ty.as_str() {
+                    "" => quote! { #stream crate::porus::fmt::fwrite(porus_sink, &mut #arg); },
+                    "c" => quote! { #stream crate::porus::io::Sink::write(porus_sink, #arg); },
+                    "s" => quote! { #stream crate::porus::fmt::String::write(#arg, porus_sink); },
+                    "b" => {
+                        let width = count(&a.width, 1);
+                        quote! { #stream crate::porus::fmt::Int::write(#arg, porus_sink, 2, #width); }
+                    }
+                    "d" => {
+                        quote! { #stream crate::porus::fmt::Int::write(#arg, porus_sink, 10, 1); }
+                    }
+                    "f" => {
+                        if let Count::Implied = a.precision {
+                            return Err("precision is required by floating number format".into());
+                        }
+                        let prec = count(&a.precision, 0);
+                        quote! { #stream crate::porus::fmt::Float::write(#arg, porus_sink, #prec); }
+                    }
+                    x => return Err(format!("unknown format: {}", x)),
+                };
+            }
+        }
+    }
+
+    Ok(quote! {
+        {
+            #[allow(unused_variables, unused_mut)]
+            let mut porus_args = (#(#args,)*);
+            #[allow(unused_variables)]
+            move |porus_sink : &mut _| {
+                #stream
+            }
+        }
+    })
+}
+
+/// Split `tokens` at the first top level comma.
+fn split_first(tokens: TokenStream) -> (TokenStream, TokenStream) {
+    let mut tokens = tokens.into_iter();
+    let first = tokens
+        .by_ref()
+        .take_while(|t| !matches!(t, TokenTree::Punct(p) if p.as_char() == ','))
+        .collect();
+    (first, tokens.collect())
+}
+
+fn expand_macro(name: &str, tokens: TokenStream) -> std::result::Result<TokenStream, String> {
+    if name == "quitf" {
+        let (verdict, tokens) = split_first(tokens);
+        let f = format(tokens)?;
+        return Ok(quote! { crate::porus::checker::quit(#verdict, #f) });
+    }
+
+    let f = format(tokens)?;
+    Ok(match name {
+        "f" => f,
+        "writef" => quote! { crate::porus::stdio::write(#f) },
+        "writelnf" => quote! { crate::porus::stdio::writeln(#f) },
+        "stringf" => quote! {
+            {
+                let mut buffer: crate::porus::string::StringBuffer = Default::default();
+                crate::porus::fmt::fwrite(&mut buffer, &mut #f);
+                let string: crate::porus::string::String = From::from(buffer);
+                string
+            }
+        },
+        _ => unreachable!(),
+    })
+}
+
+/// Macros of `porus` which are implemented with procedural macros
+/// and have to be expanded before bundling, as `(module, name)`.
+pub const MACROS: [(&str, &str); 5] = [
+    ("fmt", "f"),
+    ("stdio", "writef"),
+    ("stdio", "writelnf"),
+    ("string", "stringf"),
+    ("checker", "quitf"),
+];
+
+/// Expand every invocation of [`MACROS`] in `tokens`, innermost
+/// first.
+pub fn expand(tokens: TokenStream) -> std::result::Result<TokenStream, String> {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut result = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match &tokens[i] {
+            TokenTree::Group(g) => {
+                let mut group = Group::new(g.delimiter(), expand(g.stream())?);
+                group.set_span(g.span());
+                result.push(TokenTree::Group(group));
+            }
+            TokenTree::Ident(ident) => match (tokens.get(i + 1), tokens.get(i + 2)) {
+                (Some(TokenTree::Punct(p)), Some(TokenTree::Group(g)))
+                    if p.as_char() == '!'
+                        && g.delimiter() != Delimiter::None
+                        && MACROS.iter().any(|&(_, name)| ident == name) =>
+                {
+                    let expanded = expand_macro(&ident.to_string(), expand(g.stream())?)?;
+                    result.push(TokenTree::Group(Group::new(
+                        Delimiter::Parenthesis,
+                        expanded,
+                    )));
+                    i += 2;
+                }
+                _ => result.push(tokens[i].clone()),
+            },
+            t => result.push(t.clone()),
+        }
+        i += 1;
+    }
+
+    Ok(result.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_format, split_first, Count, Piece};
+
+    fn arguments(s: &str) -> Vec<(usize, String)> {
+        parse_format(s)
+            .unwrap()
+            .into_iter()
+            .filter_map(|p| match p {
+                Piece::Argument(a) => Some((a.position, a.ty)),
+                Piece::String(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_positional() {
+        let args = arguments("{1:d} {:d} {0:d} {:d}");
+        let positions: Vec<usize> = args.iter().map(|a| a.0).collect();
+        assert_eq!(positions, vec![1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_escape() {
+        match parse_format("{{}}").unwrap().as_slice() {
+            [Piece::String(s)] => assert_eq!(s, "{}"),
+            _ => panic!("expected a single string piece"),
+        }
+    }
+
+    #[test]
+    fn test_precision_param() {
+        match parse_format("{:.*f}").unwrap().as_slice() {
+            [Piece::Argument(a)] => {
+                assert_eq!(a.position, 1);
+                assert!(matches!(a.precision, Count::Param(0)));
+            }
+            _ => panic!("expected a single argument"),
+        }
+    }
+
+    #[test]
+    fn test_width() {
+        match parse_format("{:8b}").unwrap().as_slice() {
+            [Piece::Argument(a)] => assert!(matches!(a.width, Count::Is(8))),
+            _ => panic!("expected a single argument"),
+        }
+    }
+
+    #[test]
+    fn test_split_first() {
+        let (verdict, rest) = split_first(quote! { Verdict::WrongAnswer, "{:d}", f(a, b) });
+        assert_eq!(verdict.to_string(), quote! { Verdict::WrongAnswer }.to_string());
+        assert_eq!(rest.to_string(), quote! { "{:d}", f(a, b) }.to_string());
+    }
+}
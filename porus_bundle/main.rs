@@ -0,0 +1,224 @@
+#![deny(clippy::all)]
+#![deny(clippy::pedantic)]
+#![deny(clippy::style)]
+#![deny(clippy::complexity)]
+#![deny(clippy::perf)]
+#![deny(clippy::correctness)]
+#![deny(stable_features)]
+#![allow(clippy::uninlined_format_args)]
+#![allow(clippy::unnecessary_map_or)]
+
+//! Bundle a solution and the parts of [`porus`] it uses into a
+//! single source file, for online judges which accept only source
+//! code.
+//!
+//! ```console
+//! $ cargo run --manifest-path porus_bundle/Cargo.toml -- \
+//!     -o ITP1_1_A.rs solutions/judge.u-aizu.ac.jp/ITP1/ITP1_1_A.rs
+//! Length: 22646 (limit 65536)
+//! $
+//! ```
+//!
+//! Invocations of `f!`, `writef!`, `writelnf!`, `stringf!` and
+//! `#[leetcode]` are expanded in place, since `porus_macros` cannot
+//! be bundled. `porus` is inlined as `mod porus`, with tests, doc
+//! comments, items disabled by `cfg(feature = "...")` and items
+//! not reachable from the solution removed, see [`prune`], and the
+//! remaining `cfg(feature = "...")` resolved against `--feature`.
+
+extern crate proc_macro2;
+extern crate syn;
+#[macro_use]
+extern crate quote;
+
+mod format;
+#[path = "../porus_macros/leetcode.rs"]
+mod leetcode;
+mod module;
+mod print;
+mod prune;
+
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use std::collections::BTreeSet;
+use std::fs::{read_to_string, write};
+use std::path::PathBuf;
+use std::process::exit;
+use syn::{parse_file, parse_str, Attribute, File, Item};
+
+const USAGE: &str = "usage: porus-bundle [--porus DIR] [--feature FEATURE]... [--keep MODULE]... [--limit BYTES] [-o OUTPUT] SOLUTION";
+
+struct Options {
+    porus: PathBuf,
+    features: Vec<String>,
+    keep: Vec<String>,
+    limit: usize,
+    output: Option<PathBuf>,
+    solution: PathBuf,
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    exit(2)
+}
+
+fn parse_args() -> Options {
+    let mut porus = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../src"));
+    let mut features = Vec::new();
+    let mut keep = Vec::new();
+    let mut limit = 65536;
+    let mut output = None;
+    let mut solution = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--porus" => porus = PathBuf::from(value()),
+            "--feature" => features.push(value()),
+            "--keep" => keep.push(value()),
+            "--limit" => limit = value().parse().unwrap_or_else(|_| usage()),
+            "-o" => output = Some(PathBuf::from(value())),
+            "-h" | "--help" => usage(),
+            _ if solution.is_none() && !arg.starts_with('-') => solution = Some(PathBuf::from(arg)),
+            _ => usage(),
+        }
+    }
+
+    Options {
+        porus,
+        features,
+        keep,
+        limit,
+        output,
+        solution: solution.unwrap_or_else(|| usage()),
+    }
+}
+
+fn is_leetcode(attr: &Attribute) -> bool {
+    attr.path.is_ident("leetcode")
+}
+
+/// Expand `#[leetcode]` and drop `extern crate porus`.
+fn expand_items(items: Vec<Item>) -> Vec<Item> {
+    let mut result = Vec::new();
+    for item in items {
+        match item {
+            Item::ExternCrate(ref e) if e.ident == "porus" => {}
+            Item::Impl(mut imp) if imp.attrs.iter().any(is_leetcode) => {
+                imp.attrs.retain(|a| !is_leetcode(a));
+                let tokens = leetcode::leetcode(TokenStream::new(), imp.into_token_stream());
+                let file: File = syn::parse2(tokens).expect("invalid #[leetcode] expansion");
+                result.extend(file.items);
+            }
+            item => result.push(item),
+        }
+    }
+    result
+}
+
+fn bundle(options: &Options) -> Result<String, String> {
+    let path = &options.solution;
+    let source = read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let solution = parse_file(&source).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let attrs = solution.attrs;
+    let items = expand_items(solution.items);
+    let tokens = format::expand(quote! { #(#items)* })?;
+    let idents = module::idents(tokens.clone());
+    let tokens = module::rewrite(module::rewrite_solution(tokens), &options.features, false);
+
+    let removed: Vec<(&str, &str)> = format::MACROS
+        .iter()
+        .copied()
+        .chain(Some(("leetcode", "leetcode")))
+        .collect();
+    let mut porus = module::Porus::load(&options.porus, &removed)?;
+    porus.strip_cfg(&options.features);
+    porus.filter_prelude(&idents);
+
+    let mut roots: BTreeSet<String> = module::references(tokens.clone(), "porus");
+    if idents.contains("prelude") {
+        roots.insert("prelude".to_string());
+    }
+    roots.extend(options.keep.iter().cloned());
+    porus.prune(&roots);
+    porus.prune_nested(tokens.clone(), &options.keep);
+
+    let online_judge = options.features.iter().any(|f| f == "online-judge");
+    let crate_attrs = porus.crate_attrs().cloned().collect::<Vec<_>>();
+    let prelude = if online_judge {
+        quote! { #![no_std] #![no_main] }
+    } else {
+        quote! { #![feature(main)] }
+    };
+    let porus = module::rewrite(
+        porus.into_module().into_token_stream(),
+        &options.features,
+        true,
+    );
+    let header = module::rewrite(
+        quote! { #(#crate_attrs)* #(#attrs)* },
+        &options.features,
+        false,
+    );
+
+    let mut out = String::new();
+    for part in &[
+        header,
+        prelude,
+        quote! { extern crate alloc; },
+        porus,
+        tokens,
+    ] {
+        out.push_str(&print::print(part.clone()));
+        out.push('\n');
+    }
+
+    parse_str::<File>(&out).map_err(|e| format!("bundled source does not parse: {}", e))?;
+    Ok(out)
+}
+
+fn main() {
+    let options = parse_args();
+
+    let out = bundle(&options).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(1)
+    });
+
+    match &options.output {
+        Some(path) => write(path, &out).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path.display(), e);
+            exit(1)
+        }),
+        None => print!("{}", out),
+    }
+
+    eprintln!("Length: {} (limit {})", out.len(), options.limit);
+    if out.len() > options.limit {
+        eprintln!("Length exceeds limit by {}", out.len() - options.limit);
+        exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bundle, Options};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_hello_world_size() {
+        let root = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/.."));
+        let options = Options {
+            porus: root.join("src"),
+            features: Vec::new(),
+            keep: Vec::new(),
+            limit: 65536,
+            output: None,
+            solution: root.join("solutions/judge.u-aizu.ac.jp/ITP1/ITP1_1_A.rs"),
+        };
+        let out = bundle(&options).unwrap();
+        assert!(out.len() < 24576, "hello world bundled to {} bytes", out.len());
+    }
+}
@@ -0,0 +1,701 @@
+use crate::prune::{prune, trait_items, Traits};
+use proc_macro2::{Delimiter, Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use syn::token::Brace;
+use syn::{parse_file, Attribute, File, Item, ItemMod, ItemUse, UseTree};
+
+/// Parse the file at `path`, replacing every `mod x;` with the
+/// content of the corresponding file.
+pub fn load(path: &Path) -> Result<File, String> {
+    let source = read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut file = parse_file(&source).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let dir = if ["lib", "main", "mod"].contains(&stem) {
+        path.parent().map(Path::to_path_buf).unwrap_or_default()
+    } else {
+        path.with_extension("")
+    };
+
+    load_items(&mut file.items, &dir)?;
+    Ok(file)
+}
+
+fn load_items(items: &mut Vec<Item>, dir: &Path) -> Result<(), String> {
+    for item in items {
+        if let Item::Mod(m) = item {
+            if let Some((_, items)) = &mut m.content {
+                load_items(items, &dir.join(m.ident.to_string()))?;
+            } else {
+                let mut path = dir.join(m.ident.to_string());
+                path.set_extension("rs");
+                let file = load(&path)?;
+                m.attrs.extend(file.attrs);
+                m.content = Some((Brace::default(), file.items));
+                m.semi = None;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_cfg_test(attr: &Attribute) -> bool {
+    attr.path.is_ident("cfg") && attr.tokens.to_string() == "(test)"
+}
+
+fn item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Mod(m) => &m.attrs,
+        Item::Fn(f) => &f.attrs,
+        Item::Impl(i) => &i.attrs,
+        Item::Use(u) => &u.attrs,
+        Item::Struct(s) => &s.attrs,
+        Item::Enum(e) => &e.attrs,
+        Item::Union(u) => &u.attrs,
+        Item::Trait(t) => &t.attrs,
+        Item::Type(t) => &t.attrs,
+        Item::Const(c) => &c.attrs,
+        Item::Static(s) => &s.attrs,
+        Item::Macro(m) => &m.attrs,
+        _ => &[],
+    }
+}
+
+/// Value of the predicate of `cfg`, or `None` if it depends on
+/// anything other than features, e.g. `target_os`.
+fn eval_cfg(tokens: &[TokenTree], features: &[String]) -> Option<bool> {
+    match tokens {
+        [TokenTree::Ident(name), TokenTree::Punct(p), TokenTree::Literal(lit)]
+            if name == "feature" && p.as_char() == '=' =>
+        {
+            Some(
+                features
+                    .iter()
+                    .any(|f| format!("{:?}", f) == lit.to_string()),
+            )
+        }
+        [TokenTree::Ident(op), TokenTree::Group(g)] => {
+            let args: Vec<Option<bool>> = g
+                .stream()
+                .into_iter()
+                .collect::<Vec<_>>()
+                .split(|t| is_punct(Some(t), ','))
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| eval_cfg(arg, features))
+                .collect();
+            match op.to_string().as_str() {
+                "not" => args.first().copied().flatten().map(|x| !x),
+                "all" if args.contains(&Some(false)) => Some(false),
+                "all" if args.iter().all(Option::is_some) => Some(true),
+                "any" if args.contains(&Some(true)) => Some(true),
+                "any" if args.iter().all(Option::is_some) => Some(false),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether `item` is disabled by a `cfg` on features.
+fn cfg_disabled(item: &Item, features: &[String]) -> bool {
+    item_attrs(item).iter().any(|attr| {
+        if !attr.path.is_ident("cfg") {
+            return false;
+        }
+        match attr.tokens.clone().into_iter().next() {
+            Some(TokenTree::Group(g)) => {
+                let tokens: Vec<TokenTree> = g.stream().into_iter().collect();
+                eval_cfg(&tokens, features) == Some(false)
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Drop items disabled by a `cfg` on features.
+fn strip_cfg(items: &mut Vec<Item>, features: &[String]) {
+    items.retain(|item| !cfg_disabled(item, features));
+    for item in items {
+        if let Item::Mod(ItemMod {
+            content: Some((_, items)),
+            ..
+        }) = item
+        {
+            strip_cfg(items, features);
+        }
+    }
+}
+
+/// Name of a `macro` item, which `syn` keeps as raw tokens.
+fn macro_name(item: &Item) -> Option<String> {
+    let mut tokens = item.to_token_stream().into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(ref p) if p.as_char() == '#' => {
+                tokens.next();
+            }
+            TokenTree::Ident(ref i) if i == "pub" => {
+                if let Some(TokenTree::Group(_)) = tokens.peek() {
+                    tokens.next();
+                }
+            }
+            TokenTree::Ident(ref i) if i == "macro" => {
+                return match tokens.next() {
+                    Some(TokenTree::Ident(name)) => Some(name.to_string()),
+                    _ => None,
+                };
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+fn use_root(tree: &UseTree) -> Option<String> {
+    match tree {
+        UseTree::Path(p) => Some(p.ident.to_string()),
+        UseTree::Name(n) => Some(n.ident.to_string()),
+        UseTree::Rename(r) => Some(r.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Keep only the leaves of `tree` for which `keep(path, name)` holds,
+/// where `path` is the list of segments leading to the leaf.
+fn filter_use<F>(tree: UseTree, path: &mut Vec<String>, keep: &mut F) -> Option<UseTree>
+where
+    F: FnMut(&[String], &str) -> bool,
+{
+    match tree {
+        UseTree::Path(mut p) => {
+            path.push(p.ident.to_string());
+            let tree = filter_use(*p.tree, path, keep);
+            path.pop();
+            p.tree = Box::new(tree?);
+            Some(UseTree::Path(p))
+        }
+        UseTree::Name(n) => {
+            let name = n.ident.to_string();
+            if keep(path, &name) {
+                Some(UseTree::Name(n))
+            } else {
+                None
+            }
+        }
+        UseTree::Rename(r) => {
+            let name = r.rename.to_string();
+            if keep(path, &name) {
+                Some(UseTree::Rename(r))
+            } else {
+                None
+            }
+        }
+        UseTree::Glob(g) => Some(UseTree::Glob(g)),
+        UseTree::Group(mut g) => {
+            g.items = std::mem::take(&mut g.items)
+                .into_iter()
+                .filter_map(|t| filter_use(t, path, keep))
+                .collect();
+            if g.items.is_empty() {
+                None
+            } else {
+                Some(UseTree::Group(g))
+            }
+        }
+    }
+}
+
+pub fn filter_uses<F>(items: &mut Vec<Item>, keep: &mut F)
+where
+    F: FnMut(&[String], &str) -> bool,
+{
+    *items = std::mem::take(items)
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::Use(u) => {
+                let ItemUse { tree, .. } = u.clone();
+                filter_use(tree, &mut Vec::new(), keep).map(|tree| Item::Use(ItemUse { tree, ..u }))
+            }
+            item => Some(item),
+        })
+        .collect();
+}
+
+/// `removed` lists `(module, name)` of macros already expanded by
+/// the bundler, whose definitions rely on `porus_macros`.
+fn strip(items: &mut Vec<Item>, module: &str, removed: &[(&str, &str)]) {
+    items.retain(|item| {
+        if item_attrs(item).iter().any(is_cfg_test) {
+            return false;
+        }
+
+        match item {
+            Item::ExternCrate(_) => false,
+            Item::Use(u) => use_root(&u.tree).map_or(true, |root| root != "porus_macros"),
+            _ => macro_name(item).map_or(true, |name| {
+                !removed.iter().any(|&(m, n)| m == module && n == name)
+            }),
+        }
+    });
+
+    filter_uses(items, &mut |path, name| {
+        !(path.len() == 2
+            && path[0] == "crate"
+            && removed.iter().any(|&(m, n)| path[1] == m && name == n))
+    });
+
+    for item in items {
+        if let Item::Mod(ItemMod {
+            content: Some((_, items)),
+            ..
+        }) = item
+        {
+            strip(items, module, removed);
+        }
+    }
+}
+
+fn visit(tokens: TokenStream, f: &mut dyn FnMut(&[TokenTree], usize)) {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    for (i, token) in tokens.iter().enumerate() {
+        if let TokenTree::Group(g) = token {
+            visit(g.stream(), f);
+        }
+        f(&tokens, i);
+    }
+}
+
+pub fn is_punct(token: Option<&TokenTree>, c: char) -> bool {
+    match token {
+        Some(TokenTree::Punct(p)) => p.as_char() == c,
+        _ => false,
+    }
+}
+
+fn is_ident(token: Option<&TokenTree>, s: &str) -> bool {
+    match token {
+        Some(TokenTree::Ident(i)) => i == s,
+        _ => false,
+    }
+}
+
+/// Every `IDENT` in `root :: IDENT`.
+pub fn references(tokens: TokenStream, root: &str) -> BTreeSet<String> {
+    let mut refs = BTreeSet::new();
+    visit(tokens, &mut |tokens, i| {
+        if is_ident(tokens.get(i), root)
+            && is_punct(tokens.get(i + 1), ':')
+            && is_punct(tokens.get(i + 2), ':')
+        {
+            if let Some(TokenTree::Ident(name)) = tokens.get(i + 3) {
+                refs.insert(name.to_string());
+            }
+        }
+    });
+    refs
+}
+
+/// Every identifier appearing in `tokens`.
+pub fn idents(tokens: TokenStream) -> BTreeSet<String> {
+    let mut idents = BTreeSet::new();
+    visit(tokens, &mut |tokens, i| {
+        if let TokenTree::Ident(ident) = &tokens[i] {
+            idents.insert(ident.to_string());
+        }
+    });
+    idents
+}
+
+fn traits(tokens: TokenStream) -> BTreeSet<String> {
+    let mut traits = BTreeSet::new();
+    visit(tokens, &mut |tokens, i| {
+        if is_ident(tokens.get(i), "trait") {
+            if let Some(TokenTree::Ident(name)) = tokens.get(i + 1) {
+                traits.insert(name.to_string());
+            }
+        }
+    });
+    traits
+}
+
+/// Rewrite tokens of `porus` so that they can be put in a module
+/// named `porus`, dropping doc comments and resolving `feature`
+/// predicates of `cfg` and `cfg_attr` against `features`.
+pub fn rewrite(tokens: TokenStream, features: &[String], root: bool) -> TokenStream {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut result: Vec<TokenTree> = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = &tokens[i];
+        match token {
+            TokenTree::Punct(p) if p.as_char() == '#' => {
+                let bang = usize::from(is_punct(tokens.get(i + 1), '!'));
+                if let Some(TokenTree::Group(g)) = tokens.get(i + 1 + bang) {
+                    if g.delimiter() == Delimiter::Bracket
+                        && is_ident(g.stream().into_iter().next().as_ref(), "doc")
+                    {
+                        i += 2 + bang;
+                        continue;
+                    }
+                }
+                result.push(token.clone());
+            }
+            TokenTree::Group(g) => {
+                let cfg = match result.last() {
+                    Some(TokenTree::Ident(i)) => i == "cfg" || i == "cfg_attr",
+                    _ => false,
+                };
+                let stream = if cfg {
+                    resolve_features(g.stream(), features)
+                } else {
+                    rewrite(g.stream(), features, root)
+                };
+                let mut group = Group::new(g.delimiter(), stream);
+                group.set_span(g.span());
+                result.push(TokenTree::Group(group));
+            }
+            TokenTree::Ident(ident)
+                if root
+                    && ident == "crate"
+                    && is_punct(tokens.get(i + 1), ':')
+                    && is_punct(tokens.get(i + 2), ':') =>
+            {
+                result.push(token.clone());
+                result.push(TokenTree::Punct(Punct::new(':', Spacing::Joint)));
+                result.push(TokenTree::Punct(Punct::new(':', Spacing::Alone)));
+                result.push(TokenTree::Ident(Ident::new("porus", ident.span())));
+            }
+            _ => result.push(token.clone()),
+        }
+        i += 1;
+    }
+
+    result.into_iter().collect()
+}
+
+fn resolve_features(tokens: TokenStream, features: &[String]) -> TokenStream {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut result = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match (&tokens[i], tokens.get(i + 1), tokens.get(i + 2)) {
+            (TokenTree::Ident(ident), Some(TokenTree::Punct(p)), Some(TokenTree::Literal(lit)))
+                if ident == "feature" && p.as_char() == '=' =>
+            {
+                let name = lit.to_string();
+                let enabled = features.iter().any(|f| format!("{:?}", f) == name);
+                let op = if enabled { "all" } else { "any" };
+                result.push(TokenTree::Ident(Ident::new(op, ident.span())));
+                result.push(TokenTree::Group(Group::new(
+                    Delimiter::Parenthesis,
+                    TokenStream::new(),
+                )));
+                i += 3;
+            }
+            (TokenTree::Group(g), _, _) => {
+                let mut group = Group::new(g.delimiter(), resolve_features(g.stream(), features));
+                group.set_span(g.span());
+                result.push(TokenTree::Group(group));
+                i += 1;
+            }
+            (token, _, _) => {
+                result.push(token.clone());
+                i += 1;
+            }
+        }
+    }
+
+    result.into_iter().collect()
+}
+
+const KEYWORDS: [&str; 19] = [
+    "as", "box", "break", "const", "dyn", "else", "for", "if", "impl", "in", "let", "loop",
+    "match", "mut", "pub", "return", "static", "use", "while",
+];
+
+fn is_path_segment(token: &TokenTree) -> bool {
+    match token {
+        TokenTree::Ident(i) => !KEYWORDS.iter().any(|k| i == k),
+        TokenTree::Punct(p) => p.as_char() == '>',
+        _ => false,
+    }
+}
+
+/// Rewrite paths to the `porus` crate in the solution to paths to the
+/// bundled `porus` module.
+pub fn rewrite_solution(tokens: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut result: Vec<TokenTree> = Vec::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Group(g) => {
+                let mut group = Group::new(g.delimiter(), rewrite_solution(g.stream()));
+                group.set_span(g.span());
+                result.push(TokenTree::Group(group));
+                continue;
+            }
+            TokenTree::Ident(ident)
+                if ident == "porus"
+                    && is_punct(tokens.get(i + 1), ':')
+                    && is_punct(tokens.get(i + 2), ':') =>
+            {
+                let n = result.len();
+                let leading =
+                    n >= 2 && is_punct(result.get(n - 1), ':') && is_punct(result.get(n - 2), ':');
+                let crate_ident = TokenTree::Ident(Ident::new("crate", Span::call_site()));
+                if !leading {
+                    result.push(crate_ident);
+                    result.push(TokenTree::Punct(Punct::new(':', Spacing::Joint)));
+                    result.push(TokenTree::Punct(Punct::new(':', Spacing::Alone)));
+                } else if n == 2 || !is_path_segment(&result[n - 3]) {
+                    result.insert(n - 2, crate_ident);
+                }
+            }
+            _ => {}
+        }
+        result.push(token.clone());
+    }
+
+    result.into_iter().collect()
+}
+
+fn is_mod(item: &Item, name: &str) -> bool {
+    match item {
+        Item::Mod(m) => m.ident == name,
+        _ => false,
+    }
+}
+
+/// Name an item is referred to by, or `None` for items which are
+/// not, e.g. `impl` and macro invocations.
+pub fn item_name(item: &Item) -> Option<String> {
+    match item {
+        Item::Mod(m) => Some(m.ident.to_string()),
+        Item::Fn(f) => Some(f.sig.ident.to_string()),
+        Item::Struct(s) => Some(s.ident.to_string()),
+        Item::Enum(e) => Some(e.ident.to_string()),
+        Item::Union(u) => Some(u.ident.to_string()),
+        Item::Trait(t) => Some(t.ident.to_string()),
+        Item::Type(t) => Some(t.ident.to_string()),
+        Item::Const(c) => Some(c.ident.to_string()),
+        Item::Static(s) => Some(s.ident.to_string()),
+        Item::Macro(m) => m.ident.as_ref().map(ToString::to_string),
+        item => macro_name(item),
+    }
+}
+
+/// Items used by the compiler or the linker rather than by name.
+pub fn is_exported(item: &Item) -> bool {
+    const ATTRS: [&str; 6] = [
+        "no_mangle",
+        "export_name",
+        "lang",
+        "panic_handler",
+        "alloc_error_handler",
+        "global_allocator",
+    ];
+    item_attrs(item)
+        .iter()
+        .any(|a| ATTRS.iter().any(|name| a.path.is_ident(name)))
+}
+
+pub struct Porus {
+    pub attrs: Vec<Attribute>,
+    pub items: Vec<Item>,
+    modules: BTreeMap<String, TokenStream>,
+}
+
+impl Porus {
+    /// Load `lib.rs` of `porus` at `dir`, with tests and macros
+    /// in `removed` stripped.
+    pub fn load(dir: &Path, removed: &[(&str, &str)]) -> Result<Self, String> {
+        let file = load(&PathBuf::from(dir).join("lib.rs"))?;
+        let mut items = file.items;
+        strip(&mut items, "", removed);
+
+        let mut modules = BTreeMap::new();
+        for item in &mut items {
+            if let Item::Mod(m) = item {
+                let name = m.ident.to_string();
+                if let Some((_, items)) = &mut m.content {
+                    strip(items, &name, removed);
+                }
+                modules.insert(name, m.to_token_stream());
+            }
+        }
+
+        Ok(Self {
+            attrs: file.attrs,
+            items,
+            modules,
+        })
+    }
+
+    /// Drop items disabled by a `cfg` on features not in `features`.
+    pub fn strip_cfg(&mut self, features: &[String]) {
+        strip_cfg(&mut self.items, features);
+        for item in &self.items {
+            if let Item::Mod(m) = item {
+                self.modules
+                    .insert(m.ident.to_string(), m.to_token_stream());
+            }
+        }
+        let items = &self.items;
+        self.modules
+            .retain(|name, _| items.iter().any(|item| is_mod(item, name)));
+    }
+
+    /// Drop the `use` leaves of the prelude that are neither traits
+    /// nor mentioned in `idents`, so that unused modules can be
+    /// dropped.
+    pub fn filter_prelude(&mut self, idents: &BTreeSet<String>) {
+        let traits: BTreeMap<String, BTreeSet<String>> = self
+            .modules
+            .iter()
+            .map(|(name, tokens)| (name.clone(), traits(tokens.clone())))
+            .collect();
+
+        for item in &mut self.items {
+            if let Item::Mod(m) = item {
+                if m.ident != "prelude" {
+                    continue;
+                }
+                if let Some((_, items)) = &mut m.content {
+                    filter_uses(items, &mut |path, name| {
+                        if path.first().map_or(true, |root| root != "crate") {
+                            true
+                        } else if name == "self" {
+                            path.last().map_or(false, |m| idents.contains(m))
+                        } else {
+                            idents.contains(name)
+                                || path
+                                    .get(1)
+                                    .and_then(|m| traits.get(m))
+                                    .map_or(false, |t| t.contains(name))
+                        }
+                    });
+                }
+                self.modules
+                    .insert(m.ident.to_string(), m.to_token_stream());
+            }
+        }
+    }
+
+    /// Drop top level modules which are not reachable from the items
+    /// of `lib.rs` or `roots`.
+    pub fn prune(&mut self, roots: &BTreeSet<String>) {
+        let mut live = BTreeSet::new();
+        let mut queue: Vec<String> = roots.iter().cloned().collect();
+
+        for item in &self.items {
+            if let Item::Mod(_) = item {
+                continue;
+            }
+            queue.extend(idents(item.to_token_stream()));
+        }
+
+        while let Some(name) = queue.pop() {
+            if let Some(tokens) = self.modules.get(&name) {
+                if live.insert(name) {
+                    queue.extend(references(tokens.clone(), "crate"));
+                }
+            }
+        }
+
+        self.items.retain(|item| match item {
+            Item::Mod(m) => live.contains(&m.ident.to_string()),
+            _ => true,
+        });
+    }
+
+    /// Drop items of the remaining modules which are not reachable
+    /// from `solution`, see [`prune`](crate::prune), keeping every
+    /// item of the modules in `keep`.
+    pub fn prune_nested(&mut self, solution: TokenStream, keep: &[String]) {
+        let mut traits: Traits = self
+            .modules
+            .values()
+            .flat_map(|tokens| traits(tokens.clone()))
+            .map(|name| (name, None))
+            .collect();
+        trait_items(&self.items, &mut traits);
+        prune(&mut self.items, solution, &traits, keep);
+    }
+
+    /// Crate level attributes of `lib.rs` which have to be kept.
+    pub fn crate_attrs(&self) -> impl Iterator<Item = &Attribute> {
+        self.attrs
+            .iter()
+            .filter(|a| a.path.is_ident("feature") || a.path.is_ident("cfg_attr"))
+    }
+
+    pub fn into_module(self) -> ItemMod {
+        let items = self.items;
+        syn::parse_quote! {
+            #[macro_use]
+            pub mod porus {
+                #(#items)*
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cfg_disabled, strip_cfg};
+    use quote::ToTokens;
+    use syn::{parse_file, parse_str, Item};
+
+    fn features(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    fn disabled(item: &str, names: &[&str]) -> bool {
+        cfg_disabled(&parse_str::<Item>(item).unwrap(), &features(names))
+    }
+
+    #[test]
+    fn test_cfg() {
+        assert!(disabled(r#"#[cfg(feature = "a")] fn f() {}"#, &[]));
+        assert!(!disabled(r#"#[cfg(feature = "a")] fn f() {}"#, &["a"]));
+        assert!(disabled(r#"#[cfg(not(feature = "a"))] fn f() {}"#, &["a"]));
+        assert!(disabled(
+            r#"#[cfg(all(feature = "a", feature = "b"))] fn f() {}"#,
+            &["a"]
+        ));
+        assert!(!disabled(
+            r#"#[cfg(any(feature = "a", feature = "b"))] fn f() {}"#,
+            &["b"]
+        ));
+        assert!(!disabled(r#"#[cfg(target_os = "linux")] fn f() {}"#, &[]));
+        assert!(!disabled(
+            r#"#[cfg(all(feature = "a", unix))] fn f() {}"#,
+            &["a"]
+        ));
+        assert!(disabled(
+            r#"#[cfg(all(feature = "a", unix))] fn f() {}"#,
+            &[]
+        ));
+    }
+
+    #[test]
+    fn test_strip_cfg() {
+        let mut items =
+            parse_file(r#"mod m { #[cfg(feature = "a")] pub struct A; pub struct B; }"#)
+                .unwrap()
+                .items;
+        strip_cfg(&mut items, &[]);
+        let tokens = items[0].to_token_stream().to_string();
+        assert!(!tokens.contains("struct A"));
+        assert!(tokens.contains("struct B"));
+    }
+}
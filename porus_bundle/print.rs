@@ -0,0 +1,88 @@
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+
+/// Pairs of punctuations which would be lexed as a different token
+/// if printed without space in between.
+const MULTI: [&str; 24] = [
+    "&&", "||", "<<", ">>", "<=", ">=", "==", "!=", "+=", "-=", "*=", "/=", "%=", "^=", "&=", "|=",
+    "..", "::", "->", "=>", "<-", "//", "/*", "*/",
+];
+
+fn needs_space(prev: &TokenTree, next: &TokenTree) -> bool {
+    match (prev, next) {
+        (
+            TokenTree::Ident(_) | TokenTree::Literal(_),
+            TokenTree::Ident(_) | TokenTree::Literal(_),
+        ) => true,
+        (TokenTree::Punct(p), TokenTree::Punct(q)) => {
+            p.spacing() == Spacing::Alone && {
+                let pair: String = [p.as_char(), q.as_char()].iter().collect();
+                MULTI.contains(&pair.as_str())
+            }
+        }
+        _ => false,
+    }
+}
+
+fn print_stream(out: &mut String, tokens: TokenStream) {
+    let mut prev: Option<TokenTree> = None;
+
+    for token in tokens {
+        if let Some(ref prev) = prev {
+            if needs_space(prev, &token) {
+                out.push(' ');
+            }
+        }
+
+        match &token {
+            TokenTree::Group(g) => {
+                let (open, close) = match g.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                out.push_str(open);
+                print_stream(out, g.stream());
+                out.push_str(close);
+            }
+            TokenTree::Ident(i) => out.push_str(&i.to_string()),
+            TokenTree::Punct(p) => out.push(p.as_char()),
+            TokenTree::Literal(l) => out.push_str(&l.to_string()),
+        }
+
+        prev = Some(token);
+    }
+}
+
+/// Print `tokens` with as few whitespaces as possible.
+pub fn print(tokens: TokenStream) -> String {
+    let mut out = String::new();
+    print_stream(&mut out, tokens);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::print;
+
+    fn round_trip(s: &str) -> String {
+        print(s.parse().unwrap())
+    }
+
+    #[test]
+    fn test_print() {
+        assert_eq!(
+            round_trip("fn main ( ) { let x = - 1 ; }"),
+            "fn main(){let x=-1;}"
+        );
+        assert_eq!(round_trip("a && & b"), "a&& &b");
+        assert_eq!(round_trip("x as u32"), "x as u32");
+        assert_eq!(round_trip("b \"x\""), "b \"x\"");
+        assert_eq!(round_trip("fn f<'a>(x: &'a u8) {}"), "fn f<'a>(x:&'a u8){}");
+        assert_eq!(round_trip("a / *b"), "a/ *b");
+        assert_eq!(
+            round_trip("#![no_std] x: Result< *mut u8, ()>"),
+            "#![no_std]x:Result<*mut u8,()>"
+        );
+    }
+}
@@ -0,0 +1,820 @@
+//! Drop the items of `porus` which are not reachable from the
+//! solution.
+//!
+//! Names are resolved per module, following `use` declarations, so
+//! that e.g. `libc::read` keeps only `read` of `libc`. A name without
+//! a path is looked up in the module it appears in, while methods and
+//! other associated items are matched by name only, since resolving
+//! them would require type checking. Everything is conservative: a
+//! name which cannot be resolved keeps every item with that name.
+
+use crate::module::{filter_uses, is_exported, is_punct, item_name};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::{BTreeMap, BTreeSet};
+use syn::{ImplItem, Item, ItemImpl, ItemMod, TraitItem, UseTree, Visibility};
+
+type Module = Vec<String>;
+
+/// Crates other than `porus` and tools which paths may start with.
+const EXTERNAL: [&str; 5] = ["alloc", "clippy", "core", "rustfmt", "std"];
+
+/// Primitive types, whose paths lead to associated items.
+const PRIMITIVES: [&str; 17] = [
+    "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "str", "u8",
+    "u16", "u32", "u64", "u128", "usize",
+];
+
+/// Names defined and imported by a module.
+#[derive(Default)]
+struct Scope {
+    defined: BTreeSet<String>,
+    /// `use` leaves into `porus`, by the name they bind
+    imports: BTreeMap<String, Vec<(Module, String)>>,
+    globs: Vec<Module>,
+    /// names imported from other crates
+    external: BTreeSet<String>,
+}
+
+/// A `use` leaf: the segments before it, the name it imports and
+/// the name it binds.
+struct Leaf {
+    path: Vec<String>,
+    name: String,
+    alias: String,
+}
+
+fn leaves(tree: &UseTree, path: &mut Vec<String>, out: &mut Vec<Leaf>) {
+    match tree {
+        UseTree::Path(p) => {
+            path.push(p.ident.to_string());
+            leaves(&p.tree, path, out);
+            path.pop();
+        }
+        UseTree::Name(n) => out.push(Leaf {
+            path: path.clone(),
+            name: n.ident.to_string(),
+            alias: n.ident.to_string(),
+        }),
+        UseTree::Rename(r) => out.push(Leaf {
+            path: path.clone(),
+            name: r.ident.to_string(),
+            alias: r.rename.to_string(),
+        }),
+        UseTree::Glob(_) => out.push(Leaf {
+            path: path.clone(),
+            name: "*".to_string(),
+            alias: "*".to_string(),
+        }),
+        UseTree::Group(g) => {
+            for tree in &g.items {
+                leaves(tree, path, out);
+            }
+        }
+    }
+}
+
+/// Items mentioned by the code kept so far.
+#[derive(Default, Clone, PartialEq, Eq)]
+struct Used {
+    /// `(module, name)` for names resolved to a module of `porus`
+    paths: BTreeSet<(Module, String)>,
+    /// names of methods and other associated items
+    assoc: BTreeSet<String>,
+    /// names which may refer to any item, e.g. those of the solution
+    free: BTreeSet<String>,
+}
+
+impl Used {
+    fn mentions(&self, name: &str) -> bool {
+        self.assoc.contains(name) || self.free.contains(name)
+    }
+}
+
+/// Traits of `porus` with the names of their items, or `None` for
+/// traits defined by macros, whose items are not known.
+pub type Traits = BTreeMap<String, Option<BTreeSet<String>>>;
+
+pub fn trait_items(items: &[Item], out: &mut Traits) {
+    for item in items {
+        match item {
+            Item::Mod(ItemMod {
+                content: Some((_, items)),
+                ..
+            }) => trait_items(items, out),
+            Item::Trait(t) => {
+                let names = t.items.iter().filter_map(|item| match item {
+                    TraitItem::Method(m) => Some(m.sig.ident.to_string()),
+                    TraitItem::Const(c) => Some(c.ident.to_string()),
+                    TraitItem::Type(t) => Some(t.ident.to_string()),
+                    _ => None,
+                });
+                out.entry(t.ident.to_string())
+                    .or_insert_with(|| Some(BTreeSet::new()))
+                    .get_or_insert_with(BTreeSet::new)
+                    .extend(names);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Where a path before a name leads.
+enum Prefix {
+    /// no path, the name is in scope of the current module
+    None,
+    Module(Module),
+    /// another crate
+    External,
+    /// a type, so the name is an associated item
+    Type,
+    /// a module which cannot be resolved
+    Unknown,
+    /// an item of `porus` which does not exist, e.g. one disabled by
+    /// `cfg` but mentioned in the body of a macro
+    Missing,
+}
+
+struct Symbols<'a> {
+    scopes: BTreeMap<Module, Scope>,
+    traits: &'a Traits,
+    /// modules of which every item is kept
+    keep: &'a [String],
+}
+
+impl<'a> Symbols<'a> {
+    fn new(items: &[Item], traits: &'a Traits, keep: &'a [String]) -> Self {
+        let mut symbols = Self {
+            scopes: BTreeMap::new(),
+            traits,
+            keep,
+        };
+        let mut uses = Vec::new();
+        symbols.define(items, &mut Vec::new(), &mut uses);
+
+        let mut imports = Vec::new();
+        let mut external = Vec::new();
+        for (module, leaf) in uses {
+            match symbols.resolve_use(&module, &leaf.path) {
+                Some(target) => imports.push((module, leaf, target)),
+                None => external.push((module, leaf)),
+            }
+        }
+        for (module, leaf) in external {
+            let alias = if leaf.name == "self" {
+                leaf.path.last().cloned().unwrap_or_default()
+            } else {
+                leaf.alias
+            };
+            symbols.scopes.entry(module).or_default().external.insert(alias);
+        }
+        for (module, leaf, target) in imports {
+            let scope = symbols.scopes.entry(module).or_default();
+            if leaf.name == "*" {
+                scope.globs.push(target);
+            } else if leaf.name == "self" {
+                let mut parent = target;
+                if let Some(name) = parent.pop() {
+                    scope
+                        .imports
+                        .entry(name.clone())
+                        .or_default()
+                        .push((parent, name));
+                }
+            } else {
+                scope
+                    .imports
+                    .entry(leaf.alias)
+                    .or_default()
+                    .push((target, leaf.name));
+            }
+        }
+        symbols
+    }
+
+    fn define(&mut self, items: &[Item], module: &mut Module, uses: &mut Vec<(Module, Leaf)>) {
+        let mut scope = Scope::default();
+        for item in items {
+            match item {
+                Item::Mod(m) => {
+                    scope.defined.insert(m.ident.to_string());
+                    if let Some((_, items)) = &m.content {
+                        module.push(m.ident.to_string());
+                        self.define(items, module, uses);
+                        module.pop();
+                    }
+                }
+                Item::Use(u) => {
+                    let mut out = Vec::new();
+                    leaves(&u.tree, &mut Vec::new(), &mut out);
+                    uses.extend(out.into_iter().map(|leaf| (module.clone(), leaf)));
+                }
+                item => scope.defined.extend(item_name(item)),
+            }
+        }
+        self.scopes.insert(module.clone(), scope);
+    }
+
+    fn is_module(&self, path: &[String]) -> bool {
+        self.scopes.contains_key(path)
+    }
+
+    /// Module bound to `name` in `module`: a submodule, an imported
+    /// module, or a top level module.
+    fn lookup(&self, module: &[String], name: &str) -> Option<Module> {
+        let mut child = module.to_vec();
+        child.push(name.to_string());
+        if self.is_module(&child) {
+            return Some(child);
+        }
+        let imported = self
+            .scopes
+            .get(module)
+            .and_then(|s| s.imports.get(name))
+            .into_iter()
+            .flatten()
+            .map(|(m, n)| {
+                let mut path = m.clone();
+                path.push(n.clone());
+                path
+            })
+            .find(|path| self.is_module(path));
+        if imported.is_some() {
+            return imported;
+        }
+        let top = vec![name.to_string()];
+        if self.is_module(&top) {
+            Some(top)
+        } else {
+            None
+        }
+    }
+
+    /// Module named by the segments `path` in `module`, where `porus`
+    /// stands for the root, as in the solution.
+    fn resolve(&self, module: &[String], path: &[String]) -> Prefix {
+        let Some((first, rest)) = path.split_first() else {
+            return Prefix::None;
+        };
+        let mut resolved = match first.as_str() {
+            "crate" | "porus" => Vec::new(),
+            "self" => module.to_vec(),
+            "super" => {
+                let mut parent = module.to_vec();
+                parent.pop();
+                parent
+            }
+            "Self" => return Prefix::Type,
+            name if EXTERNAL.contains(&name) => return Prefix::External,
+            name => match self.lookup(module, name) {
+                Some(m) => m,
+                None if name.starts_with(char::is_uppercase) || PRIMITIVES.contains(&name) => {
+                    return Prefix::Type
+                }
+                None if self.scopes.get(module).map_or(false, |s| s.external.contains(name)) => {
+                    return Prefix::External
+                }
+                None => return Prefix::Unknown,
+            },
+        };
+        for segment in rest {
+            match segment.as_str() {
+                "super" => {
+                    resolved.pop();
+                }
+                "porus" if resolved.is_empty() => {}
+                _ => {
+                    let parent = resolved.clone();
+                    resolved.push(segment.clone());
+                    if !self.is_module(&resolved) {
+                        return if segment.starts_with(char::is_uppercase) {
+                            Prefix::Type
+                        } else if self.exists(&parent, segment) {
+                            Prefix::Unknown
+                        } else {
+                            Prefix::Missing
+                        };
+                    }
+                }
+            }
+        }
+        Prefix::Module(resolved)
+    }
+
+    /// Whether `name` may be defined in or imported into `module`.
+    fn exists(&self, module: &[String], name: &str) -> bool {
+        self.scopes.get(module).map_or(true, |scope| {
+            scope.defined.contains(name)
+                || scope.imports.contains_key(name)
+                || !scope.globs.is_empty()
+        })
+    }
+
+    /// Module a `use` path in `module` leads to, or `None` if it is
+    /// outside `porus`.
+    fn resolve_use(&self, module: &[String], path: &[String]) -> Option<Module> {
+        match self.resolve(module, path) {
+            Prefix::Module(m) => Some(m),
+            Prefix::None => Some(module.to_vec()),
+            _ => None,
+        }
+    }
+
+    /// Mark `name` in `module` as used, along with the modules on the
+    /// way to it and whatever it is imported from.
+    fn add(&self, used: &mut Used, module: Module, name: String) {
+        let mut queue = vec![(module, name)];
+        while let Some((module, name)) = queue.pop() {
+            if !used.paths.insert((module.clone(), name.clone())) {
+                continue;
+            }
+            for i in 0..module.len() {
+                queue.push((module[..i].to_vec(), module[i].clone()));
+            }
+            if let Some(scope) = self.scopes.get(&module) {
+                queue.extend(scope.imports.get(&name).into_iter().flatten().cloned());
+                queue.extend(scope.globs.iter().map(|g| (g.clone(), name.clone())));
+            }
+        }
+    }
+
+    /// Add the names mentioned by `tokens` to `used`. `module` is
+    /// `None` for the solution, whose names are all free. `base` is
+    /// the path before the group `tokens` comes from, as in
+    /// `use a::{b, c}`.
+    fn refs(&self, tokens: TokenStream, module: Option<&[String]>, base: &[String], used: &mut Used) {
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        for (i, token) in tokens.iter().enumerate() {
+            match token {
+                TokenTree::Group(g) => {
+                    let base = match path_before(&tokens, i, base) {
+                        Some(path) if !path.is_empty() => path,
+                        _ => Vec::new(),
+                    };
+                    self.refs(g.stream(), module, &base, used);
+                }
+                TokenTree::Ident(ident) => {
+                    let name = ident.to_string();
+                    if is_method(&tokens, i) {
+                        used.assoc.insert(name);
+                        continue;
+                    }
+                    let Some(path) = path_before(&tokens, i, base) else {
+                        used.assoc.insert(name);
+                        continue;
+                    };
+                    match (module, self.resolve(module.unwrap_or_default(), &path)) {
+                        (_, Prefix::External | Prefix::Missing) => {}
+                        (_, Prefix::Type) => {
+                            used.assoc.insert(name);
+                        }
+                        (None, Prefix::None) | (_, Prefix::Unknown) => {
+                            used.assoc.insert(name.clone());
+                            used.free.insert(name);
+                        }
+                        (Some(m), Prefix::None) => self.add(used, m.to_vec(), name),
+                        (_, Prefix::Module(m)) => self.add(used, m, name),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether the items `name` refers to in `module` are used, or
+    /// `name` is not an item of `porus`.
+    fn live_ref(&self, used: &Used, module: &[String], name: &str) -> bool {
+        let mut seen = BTreeSet::new();
+        let mut queue = vec![(module.to_vec(), name.to_string())];
+        let mut defined = false;
+        while let Some((module, name)) = queue.pop() {
+            if !seen.insert((module.clone(), name.clone())) {
+                continue;
+            }
+            if let Some(scope) = self.scopes.get(&module) {
+                if scope.defined.contains(&name) {
+                    defined = true;
+                    if used.paths.contains(&(module.clone(), name.clone()))
+                        || used.free.contains(&name)
+                    {
+                        return true;
+                    }
+                }
+                queue.extend(scope.imports.get(&name).into_iter().flatten().cloned());
+                queue.extend(scope.globs.iter().map(|g| (g.clone(), name.clone())));
+            }
+        }
+        !defined
+    }
+
+    /// An `impl` is needed if its trait and the types it is for are.
+    fn impl_live(&self, imp: &ItemImpl, module: &[String], used: &Used) -> bool {
+        let mut refs = Used::default();
+        if let Some((_, path, _)) = &imp.trait_ {
+            self.refs(path.to_token_stream(), Some(module), &[], &mut refs);
+        }
+        self.refs(imp.self_ty.to_token_stream(), Some(module), &[], &mut refs);
+        refs.paths.iter().all(|(m, name)| {
+            let mut path = m.clone();
+            path.push(name.clone());
+            self.is_module(&path) || self.live_ref(used, m, name)
+        })
+    }
+
+    fn live(&self, item: &Item, module: &[String], used: &Used) -> bool {
+        if self.keep.iter().any(|k| module.first() == Some(k)) || is_exported(item) {
+            return true;
+        }
+        match item {
+            Item::Impl(imp) => self.impl_live(imp, module, used),
+            Item::Use(_) => true,
+            item => item_name(item).map_or(true, |name| {
+                used.free.contains(&name)
+                    || used.paths.contains(&(module.to_vec(), name))
+            }),
+        }
+    }
+
+    /// Whether a macro invocation is needed. Since the items it
+    /// expands to are not known, it is kept if any other item of the
+    /// module is, or any of its identifiers is used.
+    fn invocation_live(&self, item: &Item, items: &[Item], module: &[String], used: &Used) -> bool {
+        let others = items.iter().any(|other| match other {
+            Item::Mod(_) | Item::Use(_) | Item::Macro(_) | Item::Verbatim(_) => false,
+            other => self.live(other, module, used),
+        });
+        others
+            || crate::module::idents(item.to_token_stream())
+                .iter()
+                .any(|name| {
+                    used.free.contains(name)
+                        || used.paths.contains(&(module.to_vec(), name.clone()))
+                })
+    }
+
+    fn item_live(&self, item: &Item, items: &[Item], module: &[String], used: &Used) -> bool {
+        match item {
+            Item::Macro(m) if m.ident.is_none() => {
+                self.keep.iter().any(|k| module.first() == Some(k))
+                    || self.invocation_live(item, items, module, used)
+            }
+            item => self.live(item, module, used),
+        }
+    }
+
+    /// Whether the `use` leaf `path::name` in `module` is needed.
+    /// Leaves outside `porus` are always kept, as their traits may be
+    /// used for method calls only, while a trait of `porus` is only
+    /// needed in scope if one of its items is mentioned.
+    fn use_needed(&self, used: &Used, module: &[String], path: &[String], name: &str) -> bool {
+        if self.resolve_use(module, path).is_none() {
+            return true;
+        }
+        let alias = if name == "self" {
+            match path.last() {
+                Some(alias) => alias.as_str(),
+                None => return true,
+            }
+        } else {
+            name
+        };
+        used.free.contains(alias)
+            || used.paths.contains(&(module.to_vec(), alias.to_string()))
+            || self.traits.get(alias).map_or(false, |items| {
+                items
+                    .as_ref()
+                    .map_or(true, |items| items.iter().any(|i| used.mentions(i)))
+            })
+    }
+
+    /// Methods and constants of an inherent `impl` are only needed if
+    /// mentioned, while a trait `impl` has to stay complete.
+    fn impl_item_live(imp: &ItemImpl, item: &ImplItem, used: &Used) -> bool {
+        if imp.trait_.is_some() {
+            return true;
+        }
+        match item {
+            ImplItem::Method(m) => used.mentions(&m.sig.ident.to_string()),
+            ImplItem::Const(c) => used.mentions(&c.ident.to_string()),
+            _ => true,
+        }
+    }
+
+    /// Add the names mentioned by `item` in `module` to `out`.
+    fn collect(&self, item: &Item, module: &mut Module, used: &Used, out: &mut Used) {
+        match item {
+            Item::Mod(m) => {
+                if let Some((_, items)) = &m.content {
+                    module.push(m.ident.to_string());
+                    for item in items {
+                        if self.item_live(item, items, module, used) {
+                            self.collect(item, module, used, out);
+                        }
+                    }
+                    module.pop();
+                }
+            }
+            Item::Use(u) => {
+                let mut out_leaves = Vec::new();
+                leaves(&u.tree, &mut Vec::new(), &mut out_leaves);
+                for leaf in out_leaves {
+                    if leaf.name == "*" || !self.use_needed(used, module, &leaf.path, &leaf.alias) {
+                        continue;
+                    }
+                    let alias = if leaf.name == "self" {
+                        leaf.path.last().cloned().unwrap_or_default()
+                    } else {
+                        leaf.alias
+                    };
+                    self.add(out, module.clone(), alias);
+                }
+            }
+            Item::Impl(imp) => {
+                let mut header = imp.clone();
+                header.items.clear();
+                self.refs(header.into_token_stream(), Some(module), &[], out);
+                for item in &imp.items {
+                    if Self::impl_item_live(imp, item, used) {
+                        self.refs(item.to_token_stream(), Some(module), &[], out);
+                    }
+                }
+            }
+            Item::Macro(m) if m.ident.is_some() => {
+                for (matcher, body) in macro_rules_arms(m.mac.tokens.clone()) {
+                    let literals = crate::module::idents(matcher);
+                    if literals.iter().all(|name| {
+                        used.mentions(name) || used.paths.contains(&(module.clone(), name.clone()))
+                    }) {
+                        self.refs(body, Some(module), &[], out);
+                    }
+                }
+            }
+            item => self.refs(item.to_token_stream(), Some(module), &[], out),
+        }
+    }
+
+    fn retain(&self, items: &mut Vec<Item>, module: &mut Module, used: &Used) {
+        let live: Vec<bool> = items
+            .iter()
+            .map(|item| self.item_live(item, items, module, used))
+            .collect();
+        let mut live = live.into_iter();
+        items.retain(|_| live.next().unwrap_or(true));
+        filter_uses(items, &mut |path, name| {
+            self.use_needed(used, module, path, name)
+        });
+
+        for item in items.iter_mut() {
+            match item {
+                Item::Mod(ItemMod {
+                    ident,
+                    content: Some((_, items)),
+                    ..
+                }) => {
+                    module.push(ident.to_string());
+                    self.retain(items, module, used);
+                    module.pop();
+                }
+                Item::Impl(imp) => {
+                    let items = std::mem::take(&mut imp.items);
+                    imp.items = items
+                        .into_iter()
+                        .filter(|item| Self::impl_item_live(imp, item, used))
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+        if !self.keep.iter().any(|k| module.first() == Some(k)) {
+            items.retain(|item| !is_hollow(item));
+        }
+    }
+}
+
+/// Whether `item` is a module left with private imports only, which
+/// nothing outside of it can refer to.
+fn is_hollow(item: &Item) -> bool {
+    match item {
+        Item::Mod(ItemMod {
+            content: Some((_, items)),
+            ..
+        }) => items.iter().all(|item| match item {
+            Item::Use(u) => matches!(u.vis, Visibility::Inherited),
+            _ => false,
+        }),
+        _ => false,
+    }
+}
+
+/// Whether the identifier at `i` follows a `.`, but not a `..`.
+fn is_method(tokens: &[TokenTree], i: usize) -> bool {
+    i >= 1
+        && is_punct(tokens.get(i - 1), '.')
+        && !(i >= 2 && is_punct(tokens.get(i - 2), '.'))
+}
+
+/// The segments of the path before the token at `i`, e.g.
+/// `["crate", "fmt"]` for `f` in `crate::fmt::f`, prefixed with
+/// `base` if the path starts the group. `None` if the path is
+/// relative to a type, as in `<T as Trait>::f` or `Vec::<T>::new`.
+fn path_before(tokens: &[TokenTree], i: usize, base: &[String]) -> Option<Vec<String>> {
+    let mut path = Vec::new();
+    let mut j = i;
+    while j >= 2 && is_punct(tokens.get(j - 1), ':') && is_punct(tokens.get(j - 2), ':') {
+        match j.checked_sub(3).and_then(|k| tokens.get(k)) {
+            Some(TokenTree::Ident(segment)) => {
+                path.push(segment.to_string());
+                j -= 3;
+            }
+            Some(TokenTree::Punct(p)) if p.as_char() == '>' => return None,
+            _ => {
+                j -= 2;
+                break;
+            }
+        }
+    }
+    path.reverse();
+    if j == 0 || is_punct(tokens.get(j - 1), ',') {
+        let mut full = base.to_vec();
+        full.extend(path);
+        Some(full)
+    } else {
+        Some(path)
+    }
+}
+
+/// Tokens a matcher of `macro_rules!` matches literally, that is,
+/// without `$x:fragment` and repetitions.
+fn literals(matcher: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = matcher.into_iter().collect();
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if is_punct(tokens.get(i), '$') {
+            i += if is_punct(tokens.get(i + 2), ':') { 4 } else { 2 };
+            continue;
+        }
+        result.push(tokens[i].clone());
+        i += 1;
+    }
+    result.into_iter().collect()
+}
+
+/// Split the body of `macro_rules!` into `(matcher, body)` pairs.
+fn macro_rules_arms(tokens: TokenStream) -> Vec<(TokenStream, TokenStream)> {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut arms = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        match (&tokens[i], tokens.get(i + 3)) {
+            (TokenTree::Group(matcher), Some(TokenTree::Group(body)))
+                if is_punct(tokens.get(i + 1), '=') && is_punct(tokens.get(i + 2), '>') =>
+            {
+                arms.push((literals(matcher.stream()), body.stream()));
+                i += 4;
+            }
+            _ => i += 1,
+        }
+    }
+    arms
+}
+
+/// Drop the items of the modules in `items` which are not reachable
+/// from `solution` or the items of the root, keeping every item of
+/// the top level modules in `keep`.
+pub fn prune(items: &mut Vec<Item>, solution: TokenStream, traits: &Traits, keep: &[String]) {
+    let symbols = Symbols::new(items, traits, keep);
+
+    let mut seed = Used::default();
+    symbols.refs(solution, None, &[], &mut seed);
+
+    let mut used = seed.clone();
+    loop {
+        let mut next = seed.clone();
+        for item in items.iter() {
+            match item {
+                Item::Mod(_) => symbols.collect(item, &mut Vec::new(), &used, &mut next),
+                item => symbols.refs(item.to_token_stream(), Some(&[]), &[], &mut next),
+            }
+        }
+        if next == used {
+            break;
+        }
+        used = next;
+    }
+
+    for item in items.iter_mut() {
+        if let Item::Mod(ItemMod {
+            ident,
+            content: Some((_, items)),
+            ..
+        }) = item
+        {
+            symbols.retain(items, &mut vec![ident.to_string()], &used);
+        }
+    }
+    items.retain(|item| match item {
+        Item::Mod(m) => keep.contains(&m.ident.to_string()) || !is_hollow(item),
+        _ => true,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prune, trait_items, Traits};
+    use quote::ToTokens;
+    use syn::parse_file;
+
+    fn pruned(porus: &str, solution: &str) -> String {
+        let mut items = parse_file(porus).unwrap().items;
+        let mut traits = Traits::new();
+        trait_items(&items, &mut traits);
+        prune(&mut items, solution.parse().unwrap(), &traits, &[]);
+        items
+            .iter()
+            .map(|item| item.to_token_stream().to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_paths() {
+        let out = pruned(
+            "mod libc { pub fn read() {} pub fn write() {} } \
+             mod stdio { use crate::libc; pub fn write() { libc::write(); } pub fn read() {} } \
+             mod buf { use core::ptr::read; pub fn fill() { read(); } }",
+            "porus::stdio::write(); buf::fill();",
+        );
+        assert!(out.contains("pub fn write () { libc :: write () ; }"));
+        assert!(!out.contains("pub fn read"));
+        assert!(out.contains("use core :: ptr :: read ;"));
+    }
+
+    #[test]
+    fn test_retain() {
+        let out = pruned(
+            "mod m { mod a { pub struct A; } mod b { pub struct B; } \
+             pub use self::a::A; pub use self::b::B as C; use core::mem::swap; }",
+            "m::A;",
+        );
+        assert_eq!(
+            out,
+            "mod m { mod a { pub struct A ; } pub use self :: a :: A ; use core :: mem :: swap ; }"
+        );
+    }
+
+    #[test]
+    fn test_macro() {
+        let out = pruned(
+            "mod stats { pub fn f() {} } \
+             mod start { use core::ptr; pub fn f() {} pub fn g() { ptr::null(); } \
+             macro_rules! start { () => { crate::start::g(); crate::alloc::f(); }; \
+             (stats) => { crate::stats::f(); }; } } \
+             mod checker { use crate::stats; use core::mem::swap; pub fn f() {} }",
+            "start!();",
+        );
+        assert!(out.contains("pub fn g"));
+        assert!(!out.contains("pub fn f"));
+        assert!(!out.contains("checker"));
+    }
+
+    #[test]
+    fn test_reexport() {
+        let out = pruned(
+            "mod list { mod iter { pub fn iter() {} pub fn unused() {} } \
+             pub use self::iter::{iter, unused}; } mod a { pub struct A; }",
+            "list::iter();",
+        );
+        assert!(out.contains("pub fn iter"));
+        assert!(out.contains("pub use self :: iter :: { iter } ;"));
+        assert!(!out.contains("unused"));
+        assert!(!out.contains("struct A"));
+    }
+
+    #[test]
+    fn test_impl() {
+        let out = pruned(
+            "mod pool { pub trait Pool { fn add(&self); } } \
+             mod chunk { use crate::pool::Pool; pub struct Chunk; pub struct Other; \
+             impl Chunk { pub fn new() -> Self { Chunk } pub fn unused(&self) {} } \
+             impl Pool for Chunk { fn add(&self) {} } impl Pool for Other { fn add(&self) {} } }",
+            "let c = chunk::Chunk::new(); pool::Pool::add(&c);",
+        );
+        assert!(out.contains("impl Pool for Chunk"));
+        assert!(out.contains("pub fn new"));
+        assert!(!out.contains("unused"));
+        assert!(!out.contains("Other"));
+    }
+
+    #[test]
+    fn test_trait_in_scope() {
+        let out = pruned(
+            "mod io { pub trait Sink { fn write(&mut self); } pub trait Source { fn next(&mut self); } } \
+             mod fmt { use crate::io::{Sink, Source}; pub fn f<S>(s: &mut S) { s.write() } }",
+            "fmt::f(x);",
+        );
+        assert!(out.contains("use crate :: io :: { Sink } ;"));
+        assert!(out.contains("trait Sink"));
+        assert!(!out.contains("Source"));
+    }
+}
@@ -0,0 +1,139 @@
+use std::convert::TryFrom;
+
+/// A whitespace separated token of output, with the precision
+/// declared by the `\x1bXf.<prec>\x1b\\` escape written before
+/// floating numbers under `local-judge`.
+#[derive(Debug, PartialEq)]
+pub struct Token<'a> {
+    pub text: &'a [u8],
+    pub prec: Option<u32>,
+}
+
+const ESCAPE: &[u8] = b"\x1bXf.";
+const TERMINATOR: &[u8] = b"\x1b\\";
+
+fn parse_escape(s: &[u8]) -> Option<(u32, usize)> {
+    if !s.starts_with(ESCAPE) {
+        return None;
+    }
+    let rest = &s[ESCAPE.len()..];
+    let end = rest
+        .windows(TERMINATOR.len())
+        .position(|w| w == TERMINATOR)?;
+    let prec = std::str::from_utf8(&rest[..end]).ok()?.parse().ok()?;
+    Some((prec, ESCAPE.len() + end + TERMINATOR.len()))
+}
+
+pub fn tokenize(s: &[u8]) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut prec = None;
+    let mut i = 0;
+
+    while i < s.len() {
+        if let Some((p, n)) = parse_escape(&s[i..]) {
+            prec = Some(p);
+            i += n;
+        } else if s[i].is_ascii_whitespace() {
+            i += 1;
+        } else {
+            let start = i;
+            i += 1;
+            while i < s.len() && !s[i].is_ascii_whitespace() && parse_escape(&s[i..]).is_none() {
+                i += 1;
+            }
+            tokens.push(Token {
+                text: &s[start..i],
+                prec: prec.take(),
+            });
+        }
+    }
+
+    tokens
+}
+
+fn parse_float(s: &[u8]) -> Option<f64> {
+    std::str::from_utf8(s).ok()?.parse().ok()
+}
+
+/// Floating numbers are accepted if either the absolute or the
+/// relative error is within `10^-prec`.
+fn float_eq(actual: f64, expected: f64, prec: u32) -> bool {
+    let eps = 10_f64.powi(-i32::try_from(prec).unwrap_or(i32::MAX));
+    let diff = (actual - expected).abs();
+    diff <= eps || diff <= eps * expected.abs()
+}
+
+/// Where the outputs differ, as `(token index, actual, expected)`.
+pub type Mismatch = (usize, String, String);
+
+fn show(token: Option<&Token>) -> String {
+    token.map_or_else(
+        || "EOF".to_string(),
+        |t| String::from_utf8_lossy(t.text).into_owned(),
+    )
+}
+
+pub fn compare(actual: &[u8], expected: &[u8]) -> Result<(), Mismatch> {
+    let actual = tokenize(actual);
+    let expected = tokenize(expected);
+
+    for i in 0..actual.len().max(expected.len()) {
+        let (a, e) = (actual.get(i), expected.get(i));
+        let ok = match (a, e) {
+            (Some(a), Some(e)) => match (a.prec, parse_float(a.text), parse_float(e.text)) {
+                (Some(prec), Some(x), Some(y)) => float_eq(x, y, prec),
+                _ => a.text == e.text,
+            },
+            _ => false,
+        };
+        if !ok {
+            return Err((i, show(a), show(e)));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, tokenize, Token};
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize(b"1 \x1bXf.3\x1b\\0.125\n"),
+            vec![
+                Token {
+                    text: b"1",
+                    prec: None
+                },
+                Token {
+                    text: b"0.125",
+                    prec: Some(3)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokens() {
+        assert!(compare(b"1 2\n3\n", b"1\n2 3").is_ok());
+        assert_eq!(
+            compare(b"1 2", b"1 3"),
+            Err((1, "2".to_string(), "3".to_string()))
+        );
+        assert_eq!(
+            compare(b"1", b"1 2"),
+            Err((1, "EOF".to_string(), "2".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_float() {
+        assert!(compare(b"\x1bXf.5\x1b\\0.33333", b"0.333333333").is_ok());
+        assert!(compare(b"\x1bXf.5\x1b\\0.33300", b"0.333333333").is_err());
+        assert!(compare(b"\x1bXf.2\x1b\\100000.01", b"100000.0001").is_ok());
+        assert!(compare(b"0.33333", b"0.333333333").is_err());
+        assert!(compare(b"\x1bXf.5", b"1").is_err());
+    }
+}
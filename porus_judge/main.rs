@@ -0,0 +1,224 @@
+#![deny(clippy::all)]
+#![deny(clippy::pedantic)]
+#![deny(clippy::style)]
+#![deny(clippy::complexity)]
+#![deny(clippy::perf)]
+#![deny(clippy::correctness)]
+#![deny(stable_features)]
+#![allow(clippy::uninlined_format_args)]
+#![allow(clippy::unnecessary_map_or)]
+// built with the same nightly as porus, older than `div_ceil` and
+// `ptr::addr_of_mut!`
+#![allow(clippy::manual_div_ceil)]
+#![allow(clippy::borrow_as_ptr)]
+
+//! Run a solution compiled with `local-judge` against sample input
+//! and output.
+//!
+//! ```console
+//! $ porus-judge target/debug/ITP1_1_A solutions/judge.u-aizu.ac.jp/ITP1/
+//! ITP1_1_A: AC 0.001s 2068KB heap 1KB
+//! $
+//! ```
+//!
+//! Each `X.in` is fed to the solution and its output compared with
+//! `X.out` token by token. Floating numbers written by
+//! `porus::fmt::Float` are preceded by a `\x1bXf.<prec>\x1b\\`
+//! escape, and are accepted if either the absolute or the relative
+//! error is within `10^-prec`. Exits with status 1 unless every case
+//! is accepted.
+//!
+//! The peak heap usage is reported by the solution itself through
+//! `PORUS_STATS`, and `--memory-limit` is passed to it as
+//! `PORUS_MEMORY_LIMIT`, see `porus::allocator::stats`. A solution
+//! aborted by going over the limit reports a peak above it, and is
+//! judged MLE.
+//!
+//! `--time-limit` applies to the CPU time of the solution, which is
+//! also the time reported, so that the verdict does not depend on
+//! the load of the machine.
+
+extern crate libc;
+
+mod compare;
+mod run;
+
+use run::Status;
+use std::fs::{read, read_dir};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::time::Duration;
+
+const USAGE: &str =
+    "usage: porus-judge [--time-limit SECONDS] [--memory-limit MEGABYTES] SOLUTION CASE...";
+
+enum Verdict {
+    Accepted,
+    WrongAnswer(compare::Mismatch),
+    TimeLimitExceeded,
+    MemoryLimitExceeded,
+    RuntimeError(String),
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    exit(2)
+}
+
+/// `X.in` files given directly, or found in the given directories.
+fn cases(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut cases = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut found: Vec<PathBuf> = read_dir(path)
+                .unwrap_or_else(|e| {
+                    eprintln!("{}: {}", path.display(), e);
+                    exit(2)
+                })
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().map_or(false, |e| e == "in"))
+                .collect();
+            found.sort();
+            cases.extend(found);
+        } else {
+            cases.push(path.clone());
+        }
+    }
+    cases
+}
+
+struct Limits {
+    time: Duration,
+    /// in bytes
+    memory: Option<u64>,
+}
+
+/// Peak heap usage in bytes written by the solution to `PORUS_STATS`,
+/// as `peak count largest`.
+fn heap(stats: &Path) -> Option<u64> {
+    let content = std::fs::read_to_string(stats).ok()?;
+    std::fs::remove_file(stats).ok();
+    content.split_whitespace().next()?.parse().ok()
+}
+
+fn judge(
+    solution: &Path,
+    input: &Path,
+    limits: &Limits,
+) -> Result<(Verdict, run::Usage, Option<u64>), String> {
+    let expected = input.with_extension("out");
+    let output = input.with_extension("actual");
+    let stats = input.with_extension("stats");
+    std::fs::remove_file(&stats).ok();
+
+    let mut envs = vec![("PORUS_STATS", stats.clone().into_os_string())];
+    if let Some(memory) = limits.memory {
+        envs.push(("PORUS_MEMORY_LIMIT", memory.to_string().into()));
+    }
+
+    let usage = run::run(solution, input, &output, &envs, limits.time)
+        .map_err(|e| format!("{}: {}", solution.display(), e))?;
+    let heap = heap(&stats);
+    let exceeded = match (heap, limits.memory) {
+        (Some(heap), Some(memory)) => heap > memory,
+        _ => false,
+    };
+    let verdict = match usage.status {
+        Status::TimedOut => Verdict::TimeLimitExceeded,
+        Status::Signaled(_) if exceeded => Verdict::MemoryLimitExceeded,
+        Status::Signaled(sig) => Verdict::RuntimeError(format!("signal {}", sig)),
+        Status::Exited(code) if code != 0 => Verdict::RuntimeError(format!("exit code {}", code)),
+        Status::Exited(_) => {
+            let actual = read(&output).map_err(|e| format!("{}: {}", output.display(), e))?;
+            let expected = read(&expected).map_err(|e| format!("{}: {}", expected.display(), e))?;
+            match compare::compare(&actual, &expected) {
+                Ok(()) => Verdict::Accepted,
+                Err(mismatch) => Verdict::WrongAnswer(mismatch),
+            }
+        }
+    };
+
+    if let Verdict::Accepted = verdict {
+        std::fs::remove_file(&output).ok();
+    }
+
+    Ok((verdict, usage, heap))
+}
+
+fn main() {
+    let mut limits = Limits {
+        time: Duration::from_secs(2),
+        memory: None,
+    };
+    let mut paths = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--time-limit" => {
+                let secs: f64 = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(|| usage());
+                limits.time = Duration::from_secs_f64(secs);
+            }
+            "--memory-limit" => {
+                let megabytes: u64 = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(|| usage());
+                limits.memory = Some(megabytes << 20);
+            }
+            "-h" | "--help" => usage(),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    if paths.len() < 2 {
+        usage();
+    }
+    let solution = paths.remove(0).canonicalize().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        exit(2)
+    });
+
+    let mut failed = 0;
+    for input in cases(&paths) {
+        let name = input
+            .file_stem()
+            .map_or_else(String::new, |s| s.to_string_lossy().into_owned());
+        let (verdict, usage, heap) = judge(&solution, &input, &limits).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            exit(2)
+        });
+
+        let summary = match heap {
+            Some(heap) => format!(
+                "{:.3}s {}KB heap {}KB",
+                usage.time.as_secs_f64(),
+                usage.memory,
+                (heap + 1023) / 1024
+            ),
+            None => format!("{:.3}s {}KB", usage.time.as_secs_f64(), usage.memory),
+        };
+        match verdict {
+            Verdict::Accepted => {
+                println!("{}: AC {}", name, summary);
+                continue;
+            }
+            Verdict::WrongAnswer((i, actual, expected)) => println!(
+                "{}: WA {} (token {}: expected {}, found {})",
+                name, summary, i, expected, actual
+            ),
+            Verdict::TimeLimitExceeded => println!("{}: TLE {}", name, summary),
+            Verdict::MemoryLimitExceeded => println!("{}: MLE {}", name, summary),
+            Verdict::RuntimeError(reason) => println!("{}: RE {} ({})", name, summary, reason),
+        }
+
+        failed += 1;
+    }
+
+    if failed > 0 {
+        exit(1);
+    }
+}
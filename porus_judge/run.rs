@@ -0,0 +1,135 @@
+use std::convert::TryFrom;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io;
+use std::mem::MaybeUninit;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+pub enum Status {
+    Exited(i32),
+    Signaled(i32),
+    TimedOut,
+}
+
+pub struct Usage {
+    pub status: Status,
+    /// CPU time, user and system
+    pub time: Duration,
+    /// peak resident set size in kilobytes
+    pub memory: u64,
+}
+
+fn max_rss(usage: &libc::rusage) -> u64 {
+    let rss = u64::try_from(usage.ru_maxrss).unwrap_or(0);
+    if cfg!(target_os = "macos") {
+        rss / 1024
+    } else {
+        rss
+    }
+}
+
+/// A solution which sleeps or blocks uses no CPU time, and is killed
+/// once the wall clock time reaches this many times the limit.
+const WALL_CLOCK_FACTOR: u32 = 3;
+
+fn duration(t: libc::timeval) -> Duration {
+    Duration::from_secs(u64::try_from(t.tv_sec).unwrap_or(0))
+        + Duration::from_micros(u64::try_from(t.tv_usec).unwrap_or(0))
+}
+
+/// CPU time used by every child waited for so far.
+fn children_time() -> Duration {
+    let mut usage = MaybeUninit::<libc::rusage>::zeroed();
+    let usage = unsafe {
+        libc::getrusage(libc::RUSAGE_CHILDREN, usage.as_mut_ptr());
+        usage.assume_init()
+    };
+    duration(usage.ru_utime) + duration(usage.ru_stime)
+}
+
+/// Run `program` with stdin and stdout redirected to `input` and
+/// `output` and `envs` set, and time it out once its CPU time goes
+/// over `limit`.
+///
+/// The CPU time is measured with `getrusage(RUSAGE_CHILDREN)` before
+/// and after the child is waited for, so that it does not depend on
+/// the load of the machine. A running child is killed by the kernel
+/// through `RLIMIT_CPU`, at least a second after its limit.
+pub fn run(
+    program: &Path,
+    input: &Path,
+    output: &Path,
+    envs: &[(&str, OsString)],
+    limit: Duration,
+) -> io::Result<Usage> {
+    let stdin = File::open(input)?;
+    let stdout = File::create(output)?;
+
+    let rlimit = libc::rlimit {
+        rlim_cur: limit.as_secs() + 2,
+        rlim_max: limit.as_secs() + 2,
+    };
+
+    let before = children_time();
+    let start = Instant::now();
+    let mut command = Command::new(program);
+    command
+        .envs(envs.iter().map(|(k, v)| (k, v)))
+        .stdin(stdin)
+        .stdout(stdout)
+        .stderr(Stdio::inherit());
+    unsafe {
+        command.pre_exec(move || {
+            if libc::setrlimit(libc::RLIMIT_CPU, &rlimit) < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(())
+            }
+        });
+    }
+    let child = command.spawn()?;
+    let pid = i32::try_from(child.id()).expect("pid overflow");
+
+    let mut killed = false;
+    loop {
+        let mut status: libc::c_int = 0;
+        let mut usage = MaybeUninit::<libc::rusage>::zeroed();
+        let ret = unsafe { libc::wait4(pid, &mut status, libc::WNOHANG, usage.as_mut_ptr()) };
+
+        if ret < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+
+        if ret == pid {
+            let usage = unsafe { usage.assume_init() };
+            let time = children_time().checked_sub(before).unwrap_or_default();
+            let status = if killed || time > limit {
+                Status::TimedOut
+            } else if libc::WIFEXITED(status) {
+                Status::Exited(libc::WEXITSTATUS(status))
+            } else {
+                Status::Signaled(libc::WTERMSIG(status))
+            };
+            return Ok(Usage {
+                status,
+                time,
+                memory: max_rss(&usage),
+            });
+        }
+
+        if !killed && start.elapsed() > limit * WALL_CLOCK_FACTOR {
+            unsafe { libc::kill(pid, libc::SIGKILL) };
+            killed = true;
+        }
+
+        sleep(Duration::from_millis(1));
+    }
+}
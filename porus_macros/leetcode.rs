@@ -0,0 +1,361 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::ToTokens;
+use syn::{
+    parse2, Error, FnArg, GenericArgument, ImplItem, ImplItemMethod, ItemImpl, Pat, PathArguments,
+    ReturnType, Type, Visibility,
+};
+
+enum Kind {
+    Scalar(Type),
+    Vec(Type),
+    VecMut(Type),
+    Vec2(Type),
+    String,
+    Strings,
+    List,
+    Tree,
+    Unit,
+}
+
+fn last_segment(ty: &Type) -> Option<(String, Option<&Type>)> {
+    if let Type::Path(p) = ty {
+        let segment = p.path.segments.last()?;
+        let arg = match &segment.arguments {
+            PathArguments::AngleBracketed(args) => match args.args.first() {
+                Some(GenericArgument::Type(t)) => Some(t),
+                _ => None,
+            },
+            _ => None,
+        };
+        Some((segment.ident.to_string(), arg))
+    } else {
+        None
+    }
+}
+
+fn classify(ty: &Type) -> Kind {
+    match ty {
+        Type::Tuple(t) if t.elems.is_empty() => return Kind::Unit,
+        Type::Reference(r) if r.mutability.is_some() => {
+            if let Kind::Vec(t) = classify(&r.elem) {
+                return Kind::VecMut(t);
+            }
+        }
+        _ => {}
+    }
+
+    match last_segment(ty) {
+        Some((ref name, None)) if name == "String" => Kind::String,
+        Some((ref name, None)) if name == "Tree" => Kind::Tree,
+        Some((ref name, Some(_))) if name == "SinglyLinkedList" => Kind::List,
+        Some((ref name, None)) if is_scalar(name) => Kind::Scalar(ty.clone()),
+        Some((ref name, Some(elem))) if name == "Vec" => match classify(elem) {
+            Kind::Scalar(t) => Kind::Vec(t),
+            Kind::Vec(t) => Kind::Vec2(t),
+            Kind::String => Kind::Strings,
+            _ => panic!("unsupported element type"),
+        },
+        _ => panic!("unsupported type"),
+    }
+}
+
+fn is_scalar(name: &str) -> bool {
+    ["i32", "i64", "u32", "u64", "f64", "bool"].contains(&name)
+}
+
+fn camel_case(name: &str) -> String {
+    let mut s = String::new();
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            s.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            s.push(c);
+        }
+    }
+    s
+}
+
+fn ident(name: &str) -> Ident {
+    Ident::new(name, Span::call_site())
+}
+
+struct Shim {
+    params: Vec<TokenStream>,
+    stmts: Vec<TokenStream>,
+    args: Vec<TokenStream>,
+    post: Vec<TokenStream>,
+}
+
+impl Shim {
+    fn param(&mut self, name: &str, ty: &Type) {
+        let c_name = camel_case(name);
+        let p = ident(&c_name);
+        let size = ident(&format!("{}Size", c_name));
+        let col_size = ident(&format!("{}ColSize", c_name));
+
+        match classify(ty) {
+            Kind::Scalar(t) => {
+                self.params.push(quote! { #p: #t });
+                self.args.push(quote! { #p });
+            }
+            Kind::Vec(t) => {
+                self.params.push(quote! { #p: *const #t, #size: i32 });
+                self.args
+                    .push(quote! { ::porus::leetcode::vec_from_c(#p, #size) });
+            }
+            Kind::VecMut(t) => {
+                let local = ident(&format!("porus_{}", name));
+                self.params.push(quote! { #p: *mut #t, #size: i32 });
+                self.stmts
+                    .push(quote! { let mut #local = ::porus::leetcode::vec_from_c(#p, #size); });
+                self.args.push(quote! { &mut #local });
+                self.post
+                    .push(quote! { ::porus::leetcode::vec_copy_to_c(&#local, #p, #size); });
+            }
+            Kind::Vec2(t) => {
+                self.params
+                    .push(quote! { #p: *const *const #t, #size: i32, #col_size: *const i32 });
+                self.args
+                    .push(quote! { ::porus::leetcode::vec2_from_c(#p, #size, #col_size) });
+            }
+            Kind::String => {
+                self.params.push(quote! { #p: *const u8 });
+                self.args
+                    .push(quote! { ::porus::leetcode::string_from_c(#p) });
+            }
+            Kind::Strings => {
+                self.params
+                    .push(quote! { #p: *const *const u8, #size: i32 });
+                self.args
+                    .push(quote! { ::porus::leetcode::strings_from_c(#p, #size) });
+            }
+            Kind::List => {
+                self.params
+                    .push(quote! { #p: *mut ::porus::leetcode::ListNode });
+                self.args
+                    .push(quote! { ::porus::leetcode::list_from_c(#p) });
+            }
+            Kind::Tree => {
+                self.params
+                    .push(quote! { #p: *mut ::porus::leetcode::TreeNode });
+                self.args
+                    .push(quote! { ::porus::leetcode::tree_from_c(#p) });
+            }
+            Kind::Unit => panic!("unsupported parameter type"),
+        }
+    }
+
+    fn ret(&mut self, ty: &Type) -> (TokenStream, TokenStream) {
+        match classify(ty) {
+            Kind::Unit => (quote! {}, quote! {}),
+            Kind::Scalar(t) => (quote! { -> #t }, quote! { porus_result }),
+            Kind::Vec(t) => {
+                self.params.push(quote! { returnSize: *mut i32 });
+                (
+                    quote! { -> *mut #t },
+                    quote! { ::porus::leetcode::vec_into_c(porus_result, returnSize) },
+                )
+            }
+            Kind::Vec2(t) => {
+                self.params
+                    .push(quote! { returnSize: *mut i32, returnColumnSizes: *mut *mut i32 });
+                (
+                    quote! { -> *mut *mut #t },
+                    quote! {
+                        ::porus::leetcode::vec2_into_c(porus_result, returnSize, returnColumnSizes)
+                    },
+                )
+            }
+            Kind::String => (
+                quote! { -> *mut u8 },
+                quote! { ::porus::leetcode::string_into_c(&porus_result) },
+            ),
+            Kind::Strings => {
+                self.params.push(quote! { returnSize: *mut i32 });
+                (
+                    quote! { -> *mut *mut u8 },
+                    quote! { ::porus::leetcode::strings_into_c(porus_result, returnSize) },
+                )
+            }
+            Kind::List => (
+                quote! { -> *mut ::porus::leetcode::ListNode },
+                quote! { ::porus::leetcode::list_into_c(porus_result) },
+            ),
+            Kind::Tree => (
+                quote! { -> *mut ::porus::leetcode::TreeNode },
+                quote! { ::porus::leetcode::tree_into_c(&porus_result) },
+            ),
+            Kind::VecMut(_) => panic!("unsupported return type"),
+        }
+    }
+}
+
+fn shim(self_ty: &Type, method: &ImplItemMethod) -> TokenStream {
+    let name = &method.sig.ident;
+    let c_name = ident(&camel_case(&name.to_string()));
+    let mut shim = Shim {
+        params: Vec::new(),
+        stmts: Vec::new(),
+        args: Vec::new(),
+        post: Vec::new(),
+    };
+
+    for input in &method.sig.inputs {
+        match input {
+            FnArg::Receiver(_) => panic!("methods with self are not supported"),
+            FnArg::Typed(arg) => match &*arg.pat {
+                Pat::Ident(p) => shim.param(&p.ident.to_string(), &arg.ty),
+                _ => panic!("unsupported parameter pattern"),
+            },
+        }
+    }
+
+    let (ret_ty, ret) = match &method.sig.output {
+        ReturnType::Default => (quote! {}, quote! {}),
+        ReturnType::Type(_, ty) => shim.ret(ty),
+    };
+
+    let Shim {
+        params,
+        stmts,
+        args,
+        post,
+    } = shim;
+
+    quote! {
+        #[allow(non_snake_case, clippy::missing_safety_doc)]
+        #[no_mangle]
+        pub unsafe extern "C" fn #c_name(#(#params),*) #ret_ty {
+            #(#stmts)*
+            #[allow(unused_variables)]
+            let porus_result = <#self_ty>::#name(#(#args),*);
+            #(#post)*
+            #ret
+        }
+    }
+}
+
+fn runner(self_ty: &Type, method: &ImplItemMethod) -> TokenStream {
+    let name = &method.sig.ident;
+    let mut stmts = Vec::new();
+    let mut args = Vec::new();
+    let mut modified = Vec::new();
+
+    for (i, input) in method.sig.inputs.iter().enumerate() {
+        let arg = ident(&format!("porus_arg{}", i));
+        let ty = match input {
+            FnArg::Receiver(_) => panic!("methods with self are not supported"),
+            FnArg::Typed(t) => &*t.ty,
+        };
+
+        if let Type::Reference(r) = ty {
+            let elem = &r.elem;
+            stmts.push(quote! { let mut #arg: #elem = ::porus::leetcode::read_arg(); });
+            args.push(quote! { &mut #arg });
+            modified.push(arg);
+        } else {
+            stmts.push(quote! { let #arg: #ty = ::porus::leetcode::read_arg(); });
+            args.push(quote! { #arg });
+        }
+    }
+
+    // the result first, then every argument modified in place, one
+    // per line
+    let result = match &method.sig.output {
+        ReturnType::Type(_, _) => Some(quote! { ::porus::leetcode::write_result(&porus_result); }),
+        ReturnType::Default => None,
+    };
+    let write = result.into_iter().chain(
+        modified
+            .iter()
+            .map(|arg| quote! { ::porus::leetcode::write_result(&#arg); }),
+    );
+
+    quote! {
+        #[allow(dead_code)]
+        pub fn __porus_leetcode_main() {
+            while ::porus::leetcode::has_next() {
+                #(#stmts)*
+                #[allow(unused_variables)]
+                let porus_result = <#self_ty>::#name(#(#args),*);
+                #(#write)*
+            }
+        }
+    }
+}
+
+fn error<T: ToTokens>(tokens: T, message: &str) -> TokenStream {
+    Error::new_spanned(tokens, message).to_compile_error()
+}
+
+pub fn leetcode(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let imp: ItemImpl = parse2(item).unwrap();
+
+    let methods: Vec<&ImplItemMethod> = imp
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Method(m) => match m.vis {
+                Visibility::Public(_) => Some(m),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    // the runner cannot tell from a test case which function it is for
+    let method = match methods.as_slice() {
+        [method] => method,
+        [] => return error(&imp, "#[leetcode] expects a pub fn in the impl"),
+        [_, second, ..] => {
+            return error(
+                second,
+                "#[leetcode] expects a single pub fn in the impl, make the others private",
+            )
+        }
+    };
+
+    let shims = methods.iter().map(|m| shim(&imp.self_ty, m));
+    let runner = runner(&imp.self_ty, method);
+
+    quote! {
+        #imp
+        #(#shims)*
+        #runner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::leetcode;
+    use proc_macro2::TokenStream;
+
+    fn expand(item: &str) -> String {
+        leetcode(TokenStream::new(), item.parse().unwrap()).to_string()
+    }
+
+    #[test]
+    fn test_runner_writes_every_modified_arg() {
+        let out = expand(
+            "impl Solution { pub fn merge(a: &mut Vec<i32>, m: i32, b: &mut Vec<i32>) -> i32 { 0 } }",
+        );
+        let runner = &out[out.find("fn __porus_leetcode_main").unwrap()..];
+        let writes: Vec<_> = runner.match_indices("write_result").collect();
+        assert_eq!(writes.len(), 3);
+        assert!(runner.contains("write_result (& porus_result)"));
+        assert!(runner.contains("write_result (& porus_arg0)"));
+        assert!(runner.contains("write_result (& porus_arg2)"));
+    }
+
+    #[test]
+    fn test_several_fns() {
+        let out =
+            expand("impl Solution { pub fn a(x: i32) -> i32 { x } pub fn b(x: i32) -> i32 { x } }");
+        assert!(out.starts_with("compile_error !"));
+        assert!(out.contains("single pub fn"));
+    }
+}
@@ -24,8 +24,14 @@ use proc_macro::TokenStream;
 
 mod common;
 mod format;
+mod leetcode;
 
 #[proc_macro]
 pub fn format(stream: TokenStream) -> TokenStream {
     format::format(stream.into()).into()
 }
+
+#[proc_macro_attribute]
+pub fn leetcode(attr: TokenStream, item: TokenStream) -> TokenStream {
+    leetcode::leetcode(attr.into(), item.into()).into()
+}
@@ -2,19 +2,16 @@
 extern crate porus;
 prelude!();
 
-fn main() {
-    let n: usize = read!();
-    let a: &mut Vec<isize> = &mut (0..n).map(|_| read!()).collect();
-
-
-    let x  = a.iter().next();
-    let y = a.iter().next();
-//    let m = a.iter().min();
-    let mut j;
-    for index in 2..x {
-        if (x%index==0) && (y%index==0){
-            j=index;
-        }
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
     }
-    writelnf!("{:d}", j);
-}
\ No newline at end of file
+}
+
+fn main() {
+    let x: u64 = read!();
+    let y: u64 = read!();
+    writelnf!("{:d}", gcd(x, y));
+}
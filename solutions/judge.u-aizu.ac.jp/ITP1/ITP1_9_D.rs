@@ -8,16 +8,15 @@ fn main() {
 
     for _ in 0..q {
         let buf: StringBuffer = read!();
-        let sc: String = From::from(buf);
-        let command = sc.as_ref();
+        let command: String = From::from(buf);
         let a: usize = read!();
         let b: usize = read!();
 
-        if command == b"replace" {
+        if command == "replace" {
             read!(s.as_mut()[a..=b].as_mut());
-        } else if command == b"reverse" {
+        } else if command == "reverse" {
             s.as_mut()[a..=b].reverse();
-        } else if command == b"print" {
+        } else if command == "print" {
             writelnf!("{:s}", unsafe {
                 core::str::from_utf8_unchecked(s.as_ref()[a..=b].as_ref())
             });
@@ -5,7 +5,7 @@ prelude!();
 fn main() {
     let n: usize = read!();
 
-    let buf = &mut VecDeque::new();
+    let buf = &mut RingBuffer::<_>::new_with_capacity(0);
 
     for _ in 0..n {
         let op: isize = read!();
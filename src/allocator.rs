@@ -139,3 +139,19 @@ impl<T, A: Alloc> pool::Pool for Pool<T, A> {
         }
     }
 }
+
+mod arena;
+pub use self::arena::Arena;
+
+mod tracking;
+pub use self::tracking::TrackingPool;
+
+#[cfg(feature = "local-judge")]
+pub mod stats;
+#[cfg(feature = "local-judge")]
+pub use self::stats::{set_limit, stats, Metered, Stats};
+
+#[cfg(feature = "static-heap")]
+mod static_heap;
+#[cfg(feature = "static-heap")]
+pub use self::static_heap::StaticHeap;
@@ -0,0 +1,230 @@
+use crate::utils::unwrap;
+use alloc::alloc::{Alloc, AllocErr, Global, Layout};
+use core::cell::Cell;
+use core::cmp::{max, min};
+use core::convert::TryFrom;
+use core::mem::{align_of, size_of};
+use core::ptr::{copy_nonoverlapping, null_mut, read, write, NonNull};
+
+struct Header {
+    next: Option<NonNull<Header>>,
+    layout: Layout,
+}
+
+/// Bump allocator carving allocations out of large blocks, which are
+/// freed all at once when the arena is dropped.
+///
+/// `Alloc` is implemented for `&Arena` as well, so that several
+/// collections can share one arena, e.g.
+/// `allocator::Pool::<T, &Arena>::new_with_allocator(&arena)`.
+/// Deallocating or reallocating the most recent allocation rolls back
+/// or extends it in place, any other deallocation is a no-op.
+pub struct Arena {
+    blocks: Cell<Option<NonNull<Header>>>,
+    current: Cell<*mut u8>,
+    end: Cell<*mut u8>,
+    last: Cell<*mut u8>,
+    block_size: Cell<usize>,
+}
+
+const DEFAULT_BLOCK_SIZE: usize = 65536;
+
+impl Arena {
+    pub const fn new() -> Self {
+        Self::with_block_size(DEFAULT_BLOCK_SIZE)
+    }
+
+    /// The first block is at least `size` bytes, and every later
+    /// block doubles the size of the previous one.
+    pub const fn with_block_size(size: usize) -> Self {
+        Self {
+            blocks: Cell::new(None),
+            current: Cell::new(null_mut()),
+            end: Cell::new(null_mut()),
+            last: Cell::new(null_mut()),
+            block_size: Cell::new(size),
+        }
+    }
+
+    fn remain(&self, from: *mut u8) -> usize {
+        unwrap(usize::try_from(unsafe { self.end.get().offset_from(from) }))
+    }
+
+    fn bump(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let current = self.current.get();
+        if current.is_null() {
+            return None;
+        }
+
+        let offset = current.align_offset(layout.align());
+        if usize::checked_add(offset, layout.size())? > self.remain(current) {
+            return None;
+        }
+
+        unsafe {
+            let ptr = current.add(offset);
+            self.current.set(ptr.add(layout.size()));
+            self.last.set(ptr);
+            NonNull::new(ptr)
+        }
+    }
+
+    unsafe fn grow(&self, layout: Layout) -> Result<(), AllocErr> {
+        let min_size = usize::checked_add(size_of::<Header>(), layout.size())
+            .and_then(|x| usize::checked_add(x, layout.align()))
+            .ok_or(AllocErr)?;
+        let size = max(self.block_size.get(), min_size);
+        let block_layout =
+            Layout::from_size_align(size, align_of::<Header>()).map_err(|_| AllocErr)?;
+
+        let block = Alloc::alloc(&mut Global, block_layout)?.cast::<Header>();
+        write(
+            block.as_ptr(),
+            Header {
+                next: self.blocks.get(),
+                layout: block_layout,
+            },
+        );
+
+        self.blocks.set(Some(block));
+        self.current.set(block.as_ptr().add(1).cast());
+        self.end.set(block.cast::<u8>().as_ptr().add(size));
+        self.last.set(null_mut());
+        self.block_size.set(usize::saturating_mul(size, 2));
+        Ok(())
+    }
+
+    fn is_last(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        ptr.as_ptr() == self.last.get()
+            && unsafe { ptr.as_ptr().add(layout.size()) } == self.current.get()
+    }
+
+    unsafe fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        if let Some(ptr) = self.bump(layout) {
+            return Ok(ptr);
+        }
+
+        self.grow(layout)?;
+        self.bump(layout).ok_or(AllocErr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if self.is_last(ptr, layout) {
+            self.current.set(ptr.as_ptr());
+            self.last.set(null_mut());
+        }
+    }
+
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize,
+    ) -> Result<NonNull<u8>, AllocErr> {
+        if self.is_last(ptr, layout) && new_size <= self.remain(ptr.as_ptr()) {
+            self.current.set(ptr.as_ptr().add(new_size));
+            return Ok(ptr);
+        }
+
+        let new_layout = Layout::from_size_align(new_size, layout.align()).map_err(|_| AllocErr)?;
+        let new_ptr = self.allocate(new_layout)?;
+        copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), min(layout.size(), new_size));
+        Ok(new_ptr)
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        let mut blocks = self.blocks.get();
+        while let Some(block) = blocks {
+            unsafe {
+                let header = read(block.as_ptr());
+                Alloc::dealloc(&mut Global, block.cast(), header.layout);
+                blocks = header.next;
+            }
+        }
+    }
+}
+
+unsafe impl<'a> Alloc for &'a Arena {
+    unsafe fn alloc(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        Arena::allocate(self, layout)
+    }
+
+    unsafe fn dealloc(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        Arena::deallocate(self, ptr, layout)
+    }
+
+    unsafe fn realloc(
+        &mut self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize,
+    ) -> Result<NonNull<u8>, AllocErr> {
+        Arena::reallocate(self, ptr, layout, new_size)
+    }
+}
+
+unsafe impl Alloc for Arena {
+    unsafe fn alloc(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        Arena::allocate(self, layout)
+    }
+
+    unsafe fn dealloc(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        Arena::deallocate(self, ptr, layout)
+    }
+
+    unsafe fn realloc(
+        &mut self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize,
+    ) -> Result<NonNull<u8>, AllocErr> {
+        Arena::reallocate(self, ptr, layout, new_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Arena;
+    use crate::allocator::Pool;
+    use crate::pool;
+    use alloc::alloc::{Alloc, Layout};
+
+    #[test]
+    fn test_pool() {
+        let arena = Arena::with_block_size(64);
+        let mut a: Pool<usize, &Arena> = Pool::new_with_allocator(&arena);
+        let mut b: Pool<u8, &Arena> = Pool::new_with_allocator(&arena);
+        let ha: alloc::vec::Vec<_> = (0..100).map(|i| pool::add(&mut a, i)).collect();
+        let hb = pool::add(&mut b, 42);
+        for (i, &h) in ha.iter().enumerate() {
+            assert!(*pool::get(&a, h) == i);
+        }
+        assert!(*pool::get(&b, hb) == 42);
+    }
+
+    #[test]
+    fn test_realloc_in_place() {
+        let mut arena = Arena::new();
+        unsafe {
+            let layout = Layout::from_size_align(8, 8).unwrap();
+            let p = Alloc::alloc(&mut arena, layout).unwrap();
+            assert!(p.as_ptr().align_offset(8) == 0);
+            let q = Alloc::realloc(&mut arena, p, layout, 64).unwrap();
+            assert!(p == q);
+
+            let r = Alloc::alloc(&mut arena, Layout::from_size_align(1, 1).unwrap()).unwrap();
+            let layout = Layout::from_size_align(64, 8).unwrap();
+            let s = Alloc::realloc(&mut arena, q, layout, 128).unwrap();
+            assert!(s != q);
+            assert!(s != r);
+        }
+    }
+}
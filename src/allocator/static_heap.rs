@@ -0,0 +1,234 @@
+//! Global allocator backed by a `static` array, whose size is set by
+//! `PORUS_HEAP_SIZE` at build time.
+//!
+//! Every allocation is rounded up to a power of two. Freed blocks are
+//! kept in a free list per size, and new blocks are bumped from the
+//! top of the heap. Reallocating within the same size, or the block
+//! at the top of the heap, is done in place, so that growing a
+//! `Block` by doubling is cheap. Once the heap is exhausted,
+//! allocations fall back to [`System`].
+
+use super::System;
+use crate::utils::unwrap;
+use alloc::alloc::{GlobalAlloc, Layout};
+use core::cmp::{max, min};
+use core::convert::TryFrom;
+use core::mem::size_of;
+use core::ptr::{copy_nonoverlapping, null_mut, NonNull};
+
+/// Size of the heap, unless `PORUS_HEAP_SIZE` is set.
+const DEFAULT_HEAP_SIZE: usize = 64 << 20;
+
+/// Parse a number of bytes. Overflowing fails the build as well.
+#[allow(
+    clippy::indexing_slicing,
+    clippy::integer_arithmetic,
+    clippy::cast_lossless
+)]
+const fn parse(s: &[u8]) -> usize {
+    let mut n = 0;
+    let mut i = 0;
+    while i < s.len() {
+        if s[i] < b'0' || s[i] > b'9' {
+            panic!("PORUS_HEAP_SIZE must be a number of bytes");
+        }
+        n = n * 10 + (s[i] - b'0') as usize;
+        i += 1;
+    }
+    n
+}
+
+const HEAP_SIZE: usize = match option_env!("PORUS_HEAP_SIZE") {
+    Some(s) => parse(s.as_bytes()),
+    None => DEFAULT_HEAP_SIZE,
+};
+
+const PAGE: usize = 4096;
+
+#[repr(align(4096))]
+struct Heap([u8; HEAP_SIZE]);
+
+static mut HEAP: Heap = Heap([0; HEAP_SIZE]);
+static mut TOP: usize = 0;
+
+struct Free {
+    next: Option<NonNull<Free>>,
+}
+
+const CLASSES: usize = usize::wrapping_mul(size_of::<usize>(), 8);
+
+static mut FREE: [Option<NonNull<Free>>; CLASSES] = [None; CLASSES];
+
+#[derive(Copy, Clone)]
+pub struct StaticHeap;
+
+/// Size of the block for `size` bytes aligned to `align`, and its
+/// index in `FREE`.
+fn class(size: usize, align: usize) -> Option<(usize, usize)> {
+    let size = max(max(size, align), size_of::<Free>()).checked_next_power_of_two()?;
+    Some((size, unwrap(usize::try_from(size.trailing_zeros()))))
+}
+
+unsafe fn contains(ptr: *mut u8) -> bool {
+    let base = HEAP.0.as_mut_ptr();
+    base <= ptr && ptr < base.add(HEAP_SIZE)
+}
+
+unsafe fn top() -> *mut u8 {
+    HEAP.0.as_mut_ptr().add(TOP)
+}
+
+unsafe fn bump(size: usize, align: usize) -> *mut u8 {
+    let offset = top().align_offset(align);
+    match usize::checked_add(TOP, offset).and_then(|x| usize::checked_add(x, size)) {
+        Some(end) if end <= HEAP_SIZE => {
+            let ptr = top().add(offset);
+            TOP = end;
+            ptr
+        }
+        _ => null_mut(),
+    }
+}
+
+unsafe impl GlobalAlloc for StaticHeap {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.align() <= PAGE {
+            if let Some((size, i)) = class(layout.size(), layout.align()) {
+                let free = unwrap(FREE.get_mut(i));
+                if let Some(block) = *free {
+                    *free = block.as_ref().next;
+                    return block.cast().as_ptr();
+                }
+
+                // blocks are aligned to their size, so that they can be
+                // reused by any allocation of the same size
+                let ptr = bump(size, min(size, PAGE));
+                if !ptr.is_null() {
+                    return ptr;
+                }
+            }
+        }
+
+        GlobalAlloc::alloc(&System, layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if !contains(ptr) {
+            return GlobalAlloc::dealloc(&System, ptr, layout);
+        }
+
+        let (size, i) = unwrap(class(layout.size(), layout.align()));
+        if ptr.add(size) == top() {
+            TOP = unwrap(usize::try_from(ptr.offset_from(HEAP.0.as_ptr())));
+            return;
+        }
+
+        let free = unwrap(FREE.get_mut(i));
+        let mut block = NonNull::new_unchecked(ptr).cast::<Free>();
+        block.as_mut().next = *free;
+        *free = Some(block);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if !contains(ptr) {
+            return GlobalAlloc::realloc(&System, ptr, layout, new_size);
+        }
+
+        let (size, _) = unwrap(class(layout.size(), layout.align()));
+        if let Some((new_block, _)) = class(new_size, layout.align()) {
+            if new_block == size {
+                return ptr;
+            }
+
+            if ptr.add(size) == top() && ptr.align_offset(min(new_block, PAGE)) == 0 {
+                let start = unwrap(usize::try_from(ptr.offset_from(HEAP.0.as_ptr())));
+                if let Some(end) = usize::checked_add(start, new_block) {
+                    if end <= HEAP_SIZE {
+                        TOP = end;
+                        return ptr;
+                    }
+                }
+            }
+        }
+
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        let new_ptr = GlobalAlloc::alloc(self, new_layout);
+        if !new_ptr.is_null() {
+            copy_nonoverlapping(ptr, new_ptr, min(layout.size(), new_size));
+            GlobalAlloc::dealloc(self, ptr, layout);
+        }
+        new_ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contains, StaticHeap, HEAP_SIZE, PAGE};
+    use alloc::alloc::{GlobalAlloc, Layout};
+
+    fn layout(size: usize, align: usize) -> Layout {
+        Layout::from_size_align(size, align).unwrap()
+    }
+
+    // a single test, since the heap is shared by every test thread
+    #[test]
+    fn test_static_heap() {
+        let a = StaticHeap;
+        unsafe {
+            // blocks of every class are aligned to their size
+            for &(size, align) in &[(1, 1), (24, 8), (100, 4), (4096, 8), (5000, 64)] {
+                let p = GlobalAlloc::alloc(&a, layout(size, align));
+                assert!(contains(p));
+                assert!(p.align_offset(size.next_power_of_two().min(PAGE)) == 0);
+                *p = 1;
+                *p.add(size - 1) = 2;
+                GlobalAlloc::dealloc(&a, p, layout(size, align));
+            }
+
+            // a block below the top is reused by the same class
+            let p = GlobalAlloc::alloc(&a, layout(32, 8));
+            let q = GlobalAlloc::alloc(&a, layout(PAGE, 8));
+            GlobalAlloc::dealloc(&a, p, layout(32, 8));
+            assert!(GlobalAlloc::alloc(&a, layout(20, 4)) == p);
+
+            // reallocating within the class, or the block at the top,
+            // is done in place
+            let q = GlobalAlloc::realloc(&a, q, layout(PAGE, 8), 4000);
+            let r = GlobalAlloc::realloc(&a, q, layout(4000, 8), PAGE * 2);
+            assert!(r == q);
+            *r.add(PAGE * 2 - 1) = 3;
+
+            // other blocks are moved, keeping their data
+            for i in 0..20 {
+                *p.add(i) = i as u8;
+            }
+            let t = GlobalAlloc::realloc(&a, p, layout(20, 4), 200);
+            assert!(contains(t) && t != p);
+            for i in 0..20 {
+                assert!(*t.add(i) == i as u8);
+            }
+            GlobalAlloc::dealloc(&a, t, layout(200, 4));
+            GlobalAlloc::dealloc(&a, r, layout(PAGE * 2, 8));
+
+            // out of the heap, allocations fall back to the system
+            let big = GlobalAlloc::alloc(&a, layout(HEAP_SIZE + 1, 8));
+            assert!(!big.is_null() && !contains(big));
+            *big.add(HEAP_SIZE) = 4;
+            let big = GlobalAlloc::realloc(&a, big, layout(HEAP_SIZE + 1, 8), HEAP_SIZE + 2);
+            assert!(!big.is_null() && *big.add(HEAP_SIZE) == 4);
+            GlobalAlloc::dealloc(&a, big, layout(HEAP_SIZE + 2, 8));
+
+            let aligned = GlobalAlloc::alloc(&a, layout(8, PAGE * 2));
+            assert!(!aligned.is_null() && !contains(aligned));
+            assert!(aligned.align_offset(PAGE * 2) == 0);
+            GlobalAlloc::dealloc(&a, aligned, layout(8, PAGE * 2));
+
+            // a block growing beyond the heap moves to the system
+            let p = GlobalAlloc::alloc(&a, layout(16, 8));
+            *p = 5;
+            let p = GlobalAlloc::realloc(&a, p, layout(16, 8), HEAP_SIZE + 1);
+            assert!(!contains(p) && *p == 5);
+            GlobalAlloc::dealloc(&a, p, layout(HEAP_SIZE + 1, 8));
+        }
+    }
+}
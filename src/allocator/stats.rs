@@ -0,0 +1,291 @@
+//! Allocation statistics, to predict memory limit exceeded verdicts
+//! locally.
+//!
+//! Under `local-judge` the global allocator is wrapped in [`Metered`],
+//! and the limit is read from `PORUS_MEMORY_LIMIT` (in bytes) at start.
+//! If `PORUS_STATS` is set, the statistics are written to the file it
+//! names when `main` returns, which `porus-judge` reports for every
+//! case.
+
+use crate::file;
+#[allow(unused_imports)]
+use crate::fmt::f;
+use crate::fmt::fwrite;
+use crate::io::TrySink;
+use crate::libc;
+use alloc::alloc::{Alloc, AllocErr, GlobalAlloc, Layout};
+use core::cmp::max;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// bytes in use
+    pub current: usize,
+    /// the most bytes ever in use
+    pub peak: usize,
+    /// number of allocations, including reallocations
+    pub count: usize,
+    /// the largest single allocation in bytes
+    pub largest: usize,
+}
+
+/// Wrapper counting the allocations of `A`, which is either a
+/// `GlobalAlloc` like [`System`](super::System) or an `Alloc`.
+///
+/// The counters are atomic, since the global allocator is shared by
+/// the threads of the test harness, but updated independently, so
+/// that [`stats`](Metered::stats) is only exact while a single thread
+/// allocates, as in a solution.
+pub struct Metered<A> {
+    inner: A,
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    count: AtomicUsize,
+    largest: AtomicUsize,
+    /// `usize::max_value()` if there is no limit
+    limit: AtomicUsize,
+}
+
+fn fetch_max(a: &AtomicUsize, value: usize) {
+    let mut old = a.load(Relaxed);
+    while old < value {
+        match a.compare_exchange_weak(old, value, Relaxed, Relaxed) {
+            Ok(_) => break,
+            Err(x) => old = x,
+        }
+    }
+}
+
+impl<A> Metered<A> {
+    pub const fn new(inner: A) -> Self {
+        Self {
+            inner,
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            count: AtomicUsize::new(0),
+            largest: AtomicUsize::new(0),
+            limit: AtomicUsize::new(usize::max_value()),
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            current: self.current.load(Relaxed),
+            peak: self.peak.load(Relaxed),
+            count: self.count.load(Relaxed),
+            largest: self.largest.load(Relaxed),
+        }
+    }
+
+    /// Abort once more than `limit` bytes would be in use.
+    pub fn set_limit(&self, limit: Option<usize>) {
+        self.limit
+            .store(limit.unwrap_or_else(usize::max_value), Relaxed);
+    }
+
+    fn check(&self, old_size: usize, new_size: usize) {
+        let current = self.current.load(Relaxed);
+        let required = usize::saturating_add(usize::wrapping_sub(current, old_size), new_size);
+        let limit = self.limit.load(Relaxed);
+        if required > limit {
+            exceeded(self.stats(), new_size, required, limit);
+        }
+    }
+
+    fn record(&self, old_size: usize, new_size: usize) {
+        let current = if new_size >= old_size {
+            let delta = usize::wrapping_sub(new_size, old_size);
+            usize::wrapping_add(self.current.fetch_add(delta, Relaxed), delta)
+        } else {
+            let delta = usize::wrapping_sub(old_size, new_size);
+            usize::wrapping_sub(self.current.fetch_sub(delta, Relaxed), delta)
+        };
+        fetch_max(&self.peak, current);
+        self.count.fetch_add(1, Relaxed);
+        fetch_max(&self.largest, new_size);
+    }
+
+    fn release(&self, size: usize) {
+        self.current.fetch_sub(size, Relaxed);
+    }
+}
+
+impl<A: Default> Default for Metered<A> {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+/// Report the allocation which would exceed the limit, along with
+/// the statistics as if it succeeded, so that `porus-judge` tells a
+/// memory limit exceeded from other aborts.
+fn exceeded(mut s: Stats, size: usize, required: usize, limit: usize) -> ! {
+    let buffer = &mut [0; 256];
+    let mut stderr = file::Sink::new(2, buffer);
+    fwrite(
+        &mut stderr,
+        f!(
+            "memory limit exceeded: allocating {:d} bytes with {:d} bytes in use, limit {:d} bytes\n",
+            size,
+            s.current,
+            limit
+        ),
+    );
+    TrySink::try_flush(&mut stderr).ok();
+
+    s.peak = required;
+    s.count = usize::wrapping_add(s.count, 1);
+    s.largest = max(s.largest, size);
+    write_stats(&s);
+    unsafe { libc::abort() }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for Metered<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.check(0, layout.size());
+        let ptr = GlobalAlloc::alloc(&self.inner, layout);
+        if !ptr.is_null() {
+            self.record(0, layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        GlobalAlloc::dealloc(&self.inner, ptr, layout);
+        self.release(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.check(layout.size(), new_size);
+        let new_ptr = GlobalAlloc::realloc(&self.inner, ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            self.record(layout.size(), new_size);
+        }
+        new_ptr
+    }
+}
+
+unsafe impl<A: Alloc> Alloc for Metered<A> {
+    unsafe fn alloc(&mut self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        self.check(0, layout.size());
+        let ptr = Alloc::alloc(&mut self.inner, layout)?;
+        self.record(0, layout.size());
+        Ok(ptr)
+    }
+
+    unsafe fn dealloc(&mut self, ptr: NonNull<u8>, layout: Layout) {
+        Alloc::dealloc(&mut self.inner, ptr, layout);
+        self.release(layout.size());
+    }
+
+    unsafe fn realloc(
+        &mut self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        new_size: usize,
+    ) -> Result<NonNull<u8>, AllocErr> {
+        self.check(layout.size(), new_size);
+        let new_ptr = Alloc::realloc(&mut self.inner, ptr, layout, new_size)?;
+        self.record(layout.size(), new_size);
+        Ok(new_ptr)
+    }
+}
+
+/// Statistics of the global allocator.
+pub fn stats() -> Stats {
+    crate::_A.stats()
+}
+
+/// Limit the bytes in use by the global allocator, e.g.
+/// `set_limit(Some(256 << 20))`.
+pub fn set_limit(limit: Option<usize>) {
+    crate::_A.set_limit(limit)
+}
+
+fn getenv(name: &[u8]) -> Option<&'static [u8]> {
+    unsafe {
+        let p = libc::getenv(name.as_ptr());
+        if p.is_null() {
+            return None;
+        }
+        let mut len = 0;
+        while *p.add(len) != 0 {
+            len = usize::wrapping_add(len, 1);
+        }
+        Some(core::slice::from_raw_parts(p, len))
+    }
+}
+
+/// Set the limit from `PORUS_MEMORY_LIMIT`.
+pub fn initialize() {
+    if let Some(value) = getenv(b"PORUS_MEMORY_LIMIT\0") {
+        let limit = value.iter().try_fold(0_usize, |n, &c| {
+            if c.is_ascii_digit() {
+                usize::checked_mul(n, 10)?.checked_add(usize::from(c - b'0'))
+            } else {
+                None
+            }
+        });
+        set_limit(Some(limit.expect("invalid PORUS_MEMORY_LIMIT")));
+    }
+}
+
+/// Write `peak count largest` of `s` to the file named by
+/// `PORUS_STATS`.
+fn write_stats(s: &Stats) {
+    if let Some(path) = getenv(b"PORUS_STATS\0") {
+        let fd = unsafe {
+            libc::open(
+                path.as_ptr(),
+                libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC,
+                0o644,
+            )
+        };
+        assert!(fd >= 0, "cannot open PORUS_STATS");
+
+        let buffer = &mut [0; 64];
+        let mut sink = file::Sink::new(fd, buffer);
+        fwrite(
+            &mut sink,
+            f!("{:d} {:d} {:d}\n", s.peak, s.count, s.largest),
+        );
+        TrySink::try_flush(&mut sink).expect("cannot write PORUS_STATS");
+        drop(sink);
+        unsafe { libc::close(fd) };
+    }
+}
+
+/// Write the statistics of the global allocator to the file named by
+/// `PORUS_STATS`.
+pub fn report() {
+    write_stats(&stats());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Metered, Stats};
+    use crate::allocator::System;
+    use alloc::alloc::{GlobalAlloc, Layout};
+
+    #[test]
+    fn test_stats() {
+        let a = Metered::new(System);
+        unsafe {
+            let p = GlobalAlloc::alloc(&a, Layout::from_size_align(100, 8).unwrap());
+            let q = GlobalAlloc::alloc(&a, Layout::from_size_align(50, 8).unwrap());
+            GlobalAlloc::dealloc(&a, p, Layout::from_size_align(100, 8).unwrap());
+            let q = GlobalAlloc::realloc(&a, q, Layout::from_size_align(50, 8).unwrap(), 80);
+            GlobalAlloc::dealloc(&a, q, Layout::from_size_align(80, 8).unwrap());
+        }
+        assert!(
+            a.stats()
+                == Stats {
+                    current: 0,
+                    peak: 150,
+                    count: 3,
+                    largest: 100,
+                }
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use super::Handle;
+use crate::pool::{self, IterablePool};
+use alloc::alloc::{Alloc, Global};
+use core::ptr::{drop_in_place, read, write, NonNull};
+
+/// Elements are linked in the order they were added, in front of
+/// their data, so that the pool can walk and drop them.
+struct Node<T> {
+    prev: Option<NonNull<Node<T>>>,
+    next: Option<NonNull<Node<T>>>,
+    data: T,
+}
+
+/// Same as [`Pool`](super::Pool), except that elements know each
+/// other, so that they can be iterated over, and the remaining ones
+/// are dropped together with the pool.
+pub struct TrackingPool<T, A: Alloc = Global> {
+    allocator: A,
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+}
+
+impl<T, A: Alloc> TrackingPool<T, A> {
+    pub fn new_with_allocator(allocator: A) -> Self {
+        Self {
+            allocator,
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Drop every element.
+    pub fn clear(&mut self) {
+        while let Some(node) = self.head {
+            unsafe {
+                self.head = node.as_ref().next;
+                drop_in_place(node.as_ptr());
+                Alloc::dealloc_one(&mut self.allocator, node);
+            }
+        }
+        self.tail = None;
+        self.len = 0;
+    }
+}
+
+impl<T, A: Alloc + Default> TrackingPool<T, A> {
+    pub fn new() -> Self {
+        Self::new_with_allocator(Default::default())
+    }
+}
+
+impl<T, A: Alloc + Default> Default for TrackingPool<T, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, A: Alloc> Drop for TrackingPool<T, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+const fn node<T>(handle: Handle) -> NonNull<Node<T>> {
+    handle.0.cast()
+}
+
+impl<T, A: Alloc> pool::Pool for TrackingPool<T, A> {
+    type Handle = Handle;
+    type Elem = T;
+
+    fn get(&self, handle: Handle) -> &T {
+        unsafe { &(*node::<T>(handle).as_ptr()).data }
+    }
+
+    fn get_mut(&mut self, handle: Handle) -> &mut T {
+        unsafe { &mut (*node::<T>(handle).as_ptr()).data }
+    }
+
+    fn add(&mut self, item: T) -> Handle {
+        unsafe {
+            let ptr = Alloc::alloc_one::<Node<T>>(&mut self.allocator).expect("alloc failed");
+            write(
+                ptr.as_ptr(),
+                Node {
+                    prev: self.tail,
+                    next: None,
+                    data: item,
+                },
+            );
+            match self.tail {
+                None => self.head = Some(ptr),
+                Some(tail) => (*tail.as_ptr()).next = Some(ptr),
+            }
+            self.tail = Some(ptr);
+            self.len = usize::wrapping_add(self.len, 1);
+            Handle(ptr.cast())
+        }
+    }
+
+    fn remove(&mut self, handle: Handle) -> T {
+        unsafe {
+            let ptr = node::<T>(handle);
+            let Node { prev, next, data } = read(ptr.as_ptr());
+            match prev {
+                None => self.head = next,
+                Some(prev) => (*prev.as_ptr()).next = next,
+            }
+            match next {
+                None => self.tail = prev,
+                Some(next) => (*next.as_ptr()).prev = prev,
+            }
+            Alloc::dealloc_one(&mut self.allocator, ptr);
+            self.len = usize::wrapping_sub(self.len, 1);
+            data
+        }
+    }
+}
+
+impl<T, A: Alloc> IterablePool for TrackingPool<T, A> {
+    fn next_handle(&self, handle: Option<Handle>) -> Option<Handle> {
+        let next = match handle {
+            None => self.head,
+            Some(h) => unsafe { (*node::<T>(h).as_ptr()).next },
+        };
+        next.map(|ptr| Handle(ptr.cast()))
+    }
+}
@@ -0,0 +1,218 @@
+use crate::block::Block;
+use crate::capacity::{DefaultPolicy, Policy};
+use crate::collection::Collection;
+use crate::list::{List, ListMut};
+use crate::stack::Stack;
+use alloc::alloc::{Alloc, Global};
+use core::iter::{Extend, FromIterator};
+
+/// Growable array on a [`Block`], which grows and shrinks as told by
+/// `P`. With `FixedPolicy` it never reallocates, and panics once the
+/// initial capacity is exceeded.
+pub struct Array<T, P: Policy = DefaultPolicy, A: Alloc = Global> {
+    size: usize,
+    data: Block<T, P, A>,
+}
+
+impl<T, P: Policy, A: Alloc> Array<T, P, A> {
+    pub fn new(allocator: A, capacity: usize) -> Self {
+        Self {
+            size: 0,
+            data: Block::new(allocator, capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    /// Insert `elem` at `index`, shifting the elements after it.
+    pub fn insert(&mut self, index: usize, elem: T) {
+        assert!(index <= self.size, "index out of bounds");
+        if self.size == self.data.capacity() {
+            assert!(
+                self.data.grow(0) > 0,
+                "array is full, its capacity policy does not grow"
+            );
+        }
+        self.data.copy(
+            index,
+            usize::wrapping_add(index, 1),
+            usize::wrapping_sub(self.size, index),
+        );
+        self.data.write(index, elem);
+        self.size = usize::wrapping_add(self.size, 1);
+    }
+
+    /// Remove the element at `index`, shifting the elements after it.
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.size, "index out of bounds");
+        let elem = self.data.read(index);
+        self.size = usize::wrapping_sub(self.size, 1);
+        self.data.copy(
+            usize::wrapping_add(index, 1),
+            index,
+            usize::wrapping_sub(self.size, index),
+        );
+        self.shrink();
+        elem
+    }
+
+    /// Drop the elements after the first `size` ones.
+    pub fn truncate(&mut self, size: usize) {
+        let old_size = self.size;
+        if size >= old_size {
+            return;
+        }
+
+        self.size = size;
+        for index in size..old_size {
+            drop(self.data.read(index));
+        }
+        self.shrink();
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    fn shrink(&mut self) {
+        self.data.shrink(self.size, None, 0);
+    }
+}
+
+impl<T, P: Policy, A: Alloc + Default> Array<T, P, A> {
+    pub fn new_with_capacity(capacity: usize) -> Self {
+        Self::new(Default::default(), capacity)
+    }
+}
+
+impl<T, P: Policy, A: Alloc + Default> Default for Array<T, P, A> {
+    fn default() -> Self {
+        Self::new_with_capacity(0)
+    }
+}
+
+impl<T, P: Policy, A: Alloc> Drop for Array<T, P, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, P: Policy, A: Alloc> Collection for Array<T, P, A> {
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<T, P: Policy, A: Alloc> List for Array<T, P, A> {
+    type Elem = T;
+
+    fn get(&self, index: usize) -> Option<&T> {
+        if index < self.size {
+            Some(self.data.get(index))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, P: Policy, A: Alloc> ListMut for Array<T, P, A> {
+    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.size {
+            Some(self.data.get_mut(index))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, P: Policy, A: Alloc> Stack for Array<T, P, A> {
+    type Elem = T;
+
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    fn push(&mut self, elem: T) {
+        let size = self.size;
+        self.insert(size, elem);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let index = usize::checked_sub(self.size, 1)?;
+        Some(self.remove(index))
+    }
+
+    fn top(&self) -> Option<&T> {
+        List::get(self, usize::checked_sub(self.size, 1)?)
+    }
+}
+
+impl<T, P: Policy, A: Alloc> Extend<T> for Array<T, P, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            Stack::push(self, elem);
+        }
+    }
+}
+
+impl<T, P: Policy, A: Alloc + Default> FromIterator<T> for Array<T, P, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut array = Self::new_with_capacity(iter.size_hint().0);
+        array.extend(iter);
+        array
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Array;
+    use crate::capacity::FixedPolicy;
+    use crate::collection;
+    use crate::list;
+    use crate::stack;
+    use alloc::alloc::Global;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut a: Array<usize> = (0..5).collect();
+        a.insert(2, 10);
+        a.insert(6, 20);
+        assert!(list::iter(&a).collect::<Vec<_>>() == [0, 1, 10, 2, 3, 4, 20]);
+
+        assert!(a.remove(0) == 0);
+        assert!(stack::pop(&mut a) == 20);
+        assert!(*stack::top(&a) == 4);
+        a.truncate(2);
+        assert!(list::iter(&a).collect::<Vec<_>>() == [1, 10]);
+    }
+
+    #[test]
+    fn test_shrink() {
+        let mut a: Array<usize> = (0..1000).collect();
+        let capacity = a.capacity();
+        a.truncate(1);
+        assert!(a.capacity() < capacity);
+        assert!(collection::size(&a) == 1);
+    }
+
+    #[test]
+    fn test_fixed() {
+        let mut a: Array<usize, FixedPolicy, Global> = Array::new_with_capacity(3);
+        a.extend(0..3);
+        assert!(a.capacity() == 3);
+        a.clear();
+        a.extend(0..3);
+        assert!(collection::size(&a) == 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "array is full")]
+    fn test_fixed_full() {
+        let mut a: Array<usize, FixedPolicy, Global> = Array::new_with_capacity(3);
+        a.extend(0..4);
+    }
+}
@@ -1,3 +1,5 @@
+use core::marker::PhantomData;
+
 pub trait Policy {
     fn initial(capacity: usize) -> usize;
     fn grow(capacity: usize) -> usize;
@@ -41,3 +43,169 @@ impl Policy for DefaultPolicy {
         Self::initial(new_capacity)
     }
 }
+
+/// Capacities double, and halve once at most a quarter is used.
+pub struct DoublingPolicy {}
+
+impl Policy for DoublingPolicy {
+    fn initial(size: usize) -> usize {
+        Ord::max(1, size)
+    }
+
+    fn grow(capacity: usize) -> usize {
+        usize::saturating_mul(Ord::max(1, capacity), 2)
+    }
+
+    fn shrink(size: usize, capacity: usize) -> usize {
+        let new_capacity = usize::saturating_mul(Self::initial(size), 2);
+        if usize::saturating_mul(new_capacity, 2) <= capacity {
+            new_capacity
+        } else {
+            capacity
+        }
+    }
+}
+
+/// Same as [`DoublingPolicy`], except that capacities are always
+/// powers of two, so that an index can be wrapped around with a mask
+/// instead of a comparison.
+pub struct PowerOfTwoPolicy {}
+
+impl Policy for PowerOfTwoPolicy {
+    fn initial(size: usize) -> usize {
+        size.checked_next_power_of_two().expect("capacity overflow")
+    }
+
+    fn grow(capacity: usize) -> usize {
+        usize::checked_mul(Self::initial(capacity), 2).expect("capacity overflow")
+    }
+
+    fn shrink(size: usize, capacity: usize) -> usize {
+        let new_capacity = usize::saturating_mul(Self::initial(size), 2);
+        if usize::saturating_mul(new_capacity, 2) <= capacity {
+            new_capacity
+        } else {
+            capacity
+        }
+    }
+}
+
+/// Capacities grow by `STEP`, e.g. when memory is tight and the final
+/// size is roughly known.
+pub struct LinearPolicy<const STEP: usize> {}
+
+impl<const STEP: usize> Policy for LinearPolicy<STEP> {
+    fn initial(size: usize) -> usize {
+        assert!(STEP > 0, "STEP must be positive");
+        Ord::max(STEP, size)
+    }
+
+    fn grow(capacity: usize) -> usize {
+        usize::checked_add(capacity, Self::initial(0)).expect("capacity overflow")
+    }
+
+    fn shrink(size: usize, capacity: usize) -> usize {
+        let new_capacity = usize::saturating_add(size, Self::initial(0));
+        if usize::saturating_add(new_capacity, STEP) < capacity {
+            new_capacity
+        } else {
+            capacity
+        }
+    }
+}
+
+/// Capacities given by `P`, but never more than `MAX`. Growing beyond
+/// `MAX` panics, instead of silently keeping the capacity.
+pub struct BoundedPolicy<P: Policy, const MAX: usize>(PhantomData<P>);
+
+impl<P: Policy, const MAX: usize> Policy for BoundedPolicy<P, MAX> {
+    fn initial(size: usize) -> usize {
+        assert!(size <= MAX, "capacity {} exceeds the bound {}", size, MAX);
+        Ord::min(P::initial(size), MAX)
+    }
+
+    fn grow(capacity: usize) -> usize {
+        assert!(capacity < MAX, "capacity reached the bound {}", MAX);
+        Ord::min(P::grow(capacity), MAX)
+    }
+
+    fn shrink(size: usize, capacity: usize) -> usize {
+        Ord::min(P::shrink(size, capacity), capacity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BoundedPolicy, DefaultPolicy, DoublingPolicy, FixedPolicy, LinearPolicy, Policy,
+        PowerOfTwoPolicy,
+    };
+
+    const SIZES: [usize; 12] = [0, 1, 2, 3, 7, 8, 9, 10, 100, 1000, 1023, 65536];
+
+    /// `initial` and `shrink` leave room for `size` elements, `grow`
+    /// grows if `growable`, and `shrink` never grows.
+    fn check<P: Policy>(growable: bool) {
+        for &size in SIZES.iter() {
+            let capacity = P::initial(size);
+            assert!(capacity >= size);
+
+            let mut c = capacity;
+            for _ in 0..5 {
+                let g = P::grow(c);
+                assert!(g >= c);
+                assert!(!growable || g > c);
+                c = g;
+            }
+
+            for s in (0..=size).rev().step_by(size / 10 + 1) {
+                let new_capacity = P::shrink(s, c);
+                assert!(s <= new_capacity && new_capacity <= c);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed() {
+        check::<FixedPolicy>(false);
+    }
+
+    #[test]
+    fn test_default() {
+        check::<DefaultPolicy>(true);
+    }
+
+    #[test]
+    fn test_doubling() {
+        check::<DoublingPolicy>(true);
+    }
+
+    #[test]
+    fn test_power_of_two() {
+        check::<PowerOfTwoPolicy>(true);
+        for &size in SIZES.iter() {
+            let capacity = PowerOfTwoPolicy::initial(size);
+            assert!(capacity.is_power_of_two());
+            assert!(PowerOfTwoPolicy::grow(capacity).is_power_of_two());
+            assert!(PowerOfTwoPolicy::shrink(size, capacity * 16).is_power_of_two());
+        }
+    }
+
+    #[test]
+    fn test_linear() {
+        check::<LinearPolicy<16>>(true);
+        assert!(LinearPolicy::<16>::grow(32) == 48);
+    }
+
+    #[test]
+    fn test_bounded() {
+        check::<BoundedPolicy<DefaultPolicy, 1_000_000>>(true);
+        assert!(BoundedPolicy::<DoublingPolicy, 100>::grow(64) == 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity reached the bound 100")]
+    fn test_bounded_full() {
+        BoundedPolicy::<DoublingPolicy, 100>::grow(100);
+    }
+}
@@ -0,0 +1,345 @@
+//! Checkers for problems with more than one correct answer,
+//! compatible with [testlib](https://github.com/MikeMirzayanov/testlib).
+//!
+//! A checker is run as `checker INPUT OUTPUT ANSWER`, where `OUTPUT`
+//! is the output of the contestant and `ANSWER` is the output of the
+//! reference solution. It reports the verdict with its exit code, 0
+//! for accepted, 1 for wrong answer, 2 for presentation error and 3
+//! for failure of the checker itself, and a message on the standard
+//! error.
+//!
+//! ```ignore
+//! #![feature(proc_macro_hygiene)]
+//! #![feature(main)]
+//! #![cfg_attr(not(debug_assertions), no_std)]
+//!
+//! #[macro_use]
+//! extern crate porus;
+//! prelude!(checker);
+//!
+//! fn main() {
+//!     let n: usize = checker::input().next();
+//!     let expected: f64 = checker::answer().next();
+//!     let found: f64 = checker::output().next();
+//!     if !checker::float_eq(expected, found, 1e-6) {
+//!         quitf!(Verdict::WrongAnswer, "expected {:.6f}, found {:.6f}", expected, found);
+//!     }
+//!     quitf!(Verdict::Accepted, "{:d} numbers", n);
+//! }
+//! ```
+//!
+//! Reading from the output of the contestant ends the check with a
+//! presentation error if the output is malformed, while reading from
+//! the input or the answer ends it with a failure.
+
+use crate::file;
+#[allow(unused_imports)]
+use crate::fmt::f;
+use crate::fmt::fwrite_str;
+use crate::io::{PeekableSource, Sink, TrySink};
+use crate::libc;
+use crate::list::get;
+use crate::math::fabs;
+use crate::scan::{fread, is_whitespace, Consumer, Whitespace};
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::ptr::null;
+use core::slice::from_raw_parts;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Accepted,
+    WrongAnswer,
+    PresentationError,
+    Fail,
+}
+
+impl Verdict {
+    pub const fn code(self) -> i32 {
+        match self {
+            Verdict::Accepted => 0,
+            Verdict::WrongAnswer => 1,
+            Verdict::PresentationError => 2,
+            Verdict::Fail => 3,
+        }
+    }
+
+    const fn prefix(self) -> &'static str {
+        match self {
+            Verdict::Accepted => "ok",
+            Verdict::WrongAnswer => "wrong answer",
+            Verdict::PresentationError => "wrong output format",
+            Verdict::Fail => "FAIL",
+        }
+    }
+}
+
+/// Write the verdict and the message written by `f` to the standard
+/// error, and exit with the code of the verdict.
+pub fn quit<F: FnMut(&mut file::Sink)>(verdict: Verdict, mut f: F) -> ! {
+    let buffer = &mut [0; 1024];
+    let mut stderr = file::Sink::new(2, buffer);
+    fwrite_str(&mut stderr, verdict.prefix());
+    Sink::write(&mut stderr, b' ');
+    f(&mut stderr);
+    Sink::write(&mut stderr, b'\n');
+    TrySink::try_flush(&mut stderr).ok();
+    unsafe { libc::exit(verdict.code()) }
+}
+
+/// Macro for ending the check, e.g.
+/// `quitf!(Verdict::WrongAnswer, "expected {:d}", x)`.
+pub macro quitf($verdict:expr, $($arg:tt)*) {
+    quit($verdict, f!($($arg)*))
+}
+
+/// End the check with a failure unless `cond` holds, e.g. to validate
+/// the input.
+pub fn ensure(cond: bool, message: &str) {
+    if !cond {
+        quit(Verdict::Fail, |s| fwrite_str(s, message));
+    }
+}
+
+/// Floating numbers are equal if either the absolute or the relative
+/// error is within `eps`.
+#[allow(clippy::float_arithmetic, clippy::float_cmp)]
+pub fn float_eq(expected: f64, found: f64, eps: f64) -> bool {
+    if expected.is_nan() || found.is_nan() {
+        return expected.is_nan() && found.is_nan();
+    }
+    if expected.is_infinite() || found.is_infinite() {
+        return expected == found;
+    }
+
+    let diff = fabs(expected - found);
+    diff <= eps || diff <= eps * fabs(expected)
+}
+
+pub struct Stream {
+    source: PeekableSource<file::Source>,
+    name: &'static str,
+    verdict: Verdict,
+}
+
+const BUFFER_SIZE: usize = 65536;
+
+impl Stream {
+    fn open(path: &[u8], name: &'static str, verdict: Verdict) -> Self {
+        let fd = open(path)
+            .unwrap_or_else(|e| quit(Verdict::Fail, f!("cannot open {:s}: {:d}", name, e.errno())));
+        let buffer = Box::leak(vec![0; BUFFER_SIZE].into_boxed_slice());
+        Self {
+            source: From::from(file::Source::new(fd, buffer)),
+            name,
+            verdict,
+        }
+    }
+
+    /// End the check with the verdict for malformed content of this
+    /// stream.
+    pub fn quit<F: FnMut(&mut file::Sink)>(&self, mut f: F) -> ! {
+        let name = self.name;
+        quit(self.verdict, |s: &mut file::Sink| {
+            fwrite_str(s, name);
+            fwrite_str(s, ": ");
+            f(s);
+        })
+    }
+
+    /// Read without skipping whitespace.
+    pub fn read<C: Consumer>(&mut self, c: C) -> bool {
+        fread(&mut self.source, c)
+    }
+
+    /// Skip whitespace and read.
+    pub fn read_skip_ws<C: Consumer>(&mut self, c: C) -> bool {
+        self.read(Whitespace);
+        self.read(c)
+    }
+
+    /// Skip whitespace and read a value, ending the check if there is
+    /// none.
+    pub fn next<T: Default>(&mut self) -> T
+    where
+        for<'a> &'a mut T: Consumer,
+    {
+        let mut x = Default::default();
+        self.read(Whitespace);
+        if self.source.eof() {
+            self.quit(|s| fwrite_str(s, "unexpected end of file"));
+        }
+        if !self.read(&mut x) {
+            self.quit(|s| fwrite_str(s, "unexpected character"));
+        }
+        x
+    }
+
+    fn expect(&mut self, c: u8, what: &str) {
+        match self.source.peek() {
+            Some(&x) if x == c => self.source.consume(),
+            _ => self.quit(|s| {
+                fwrite_str(s, "expected ");
+                fwrite_str(s, what);
+            }),
+        }
+    }
+
+    /// Expect exactly one space.
+    pub fn space(&mut self) {
+        self.expect(b' ', "space");
+    }
+
+    /// Expect exactly one newline.
+    pub fn eoln(&mut self) {
+        self.expect(b'\n', "end of line");
+    }
+
+    /// Expect the end of file, without any whitespace before it.
+    pub fn eof(&mut self) {
+        if !self.source.eof() {
+            self.quit(|s| fwrite_str(s, "expected end of file"));
+        }
+    }
+
+    /// Whether only whitespace is left.
+    pub fn seek_eof(&mut self) -> bool {
+        while let Some(&c) = self.source.peek() {
+            if !is_whitespace(c) {
+                return false;
+            }
+            self.source.consume();
+        }
+        true
+    }
+
+    /// Expect nothing but whitespace to be left.
+    pub fn skip_eof(&mut self) {
+        if !self.seek_eof() {
+            self.quit(|s| fwrite_str(s, "extra data"));
+        }
+    }
+}
+
+fn open(path: &[u8]) -> Result<i32, libc::Error> {
+    let mut s = Vec::with_capacity(usize::wrapping_add(path.len(), 1));
+    s.extend_from_slice(path);
+    s.push(0);
+    let fd = unsafe { libc::open(s.as_ptr(), libc::O_RDONLY) };
+    if fd < 0 {
+        libc::get_error()
+    } else {
+        Ok(fd)
+    }
+}
+
+static mut ARGC: usize = 0;
+static mut ARGV: *const *const u8 = null();
+
+/// Called with the arguments of the exported C `main` under
+/// `online-judge`. Otherwise arguments are read from
+/// `/proc/self/cmdline`, which exists only on Linux, so elsewhere
+/// checkers have to be built with `online-judge`.
+pub unsafe fn set_args(argc: i32, argv: *const *const u8) {
+    ARGC = usize::try_from(argc).unwrap_or(0);
+    ARGV = argv;
+}
+
+unsafe fn c_str(p: *const u8) -> &'static [u8] {
+    let mut len = 0;
+    while *p.add(len) != 0 {
+        len = usize::wrapping_add(len, 1);
+    }
+    from_raw_parts(p, len)
+}
+
+pub fn args() -> Vec<&'static [u8]> {
+    unsafe {
+        if !ARGV.is_null() {
+            return (0..ARGC).map(|i| c_str(*ARGV.add(i))).collect();
+        }
+    }
+    cmdline()
+}
+
+#[cfg(target_os = "linux")]
+fn cmdline() -> Vec<&'static [u8]> {
+    let fd = open(b"/proc/self/cmdline").expect("cannot read arguments");
+    let buffer = &mut [0; 1024];
+    let cmdline: Vec<u8> = file::Source::new(fd, buffer).collect();
+    unsafe { libc::close(fd) };
+
+    let cmdline: &'static [u8] = Box::leak(cmdline.into_boxed_slice());
+    let mut args: Vec<&'static [u8]> = cmdline.split(|&c| c == 0).collect();
+    // cmdline ends with a NUL
+    args.pop();
+    args
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cmdline() -> Vec<&'static [u8]> {
+    quit(Verdict::Fail, |s| {
+        fwrite_str(s, "arguments are only known under online-judge")
+    })
+}
+
+static mut STREAMS: Option<[Stream; 3]> = None;
+
+/// Open the input, the output and the answer given in the arguments.
+pub fn initialize() {
+    let args = args();
+    if args.len() < 4 {
+        quit(Verdict::Fail, |s| {
+            fwrite_str(s, "usage: checker INPUT OUTPUT ANSWER")
+        });
+    }
+
+    let streams = [
+        Stream::open(get(&args, 1), "input", Verdict::Fail),
+        Stream::open(get(&args, 2), "output", Verdict::PresentationError),
+        Stream::open(get(&args, 3), "answer", Verdict::Fail),
+    ];
+    unsafe {
+        STREAMS = Some(streams);
+    }
+}
+
+fn stream(i: usize) -> &'static mut Stream {
+    unsafe {
+        STREAMS
+            .as_mut()
+            .and_then(|s| s.get_mut(i))
+            .expect("checker not initialized")
+    }
+}
+
+/// The input of the test.
+pub fn input() -> &'static mut Stream {
+    stream(0)
+}
+
+/// The output of the contestant.
+pub fn output() -> &'static mut Stream {
+    stream(1)
+}
+
+/// The output of the reference solution.
+pub fn answer() -> &'static mut Stream {
+    stream(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::float_eq;
+
+    #[test]
+    fn test_float_eq() {
+        assert!(float_eq(1.0, 1.000_000_1, 1e-6));
+        assert!(float_eq(1e9, 1e9 + 1.0, 1e-6));
+        assert!(!float_eq(0.5, 0.501, 1e-6));
+        assert!(float_eq(core::f64::NAN, core::f64::NAN, 1e-6));
+        assert!(!float_eq(core::f64::INFINITY, 1e300, 1e-6));
+    }
+}
@@ -1,92 +1,357 @@
 use crate::block::Block;
 use crate::capacity::{DefaultPolicy, Policy};
-use crate::pool::{self, Pool};
+use crate::pool::{self, IterablePool, Pool};
+use crate::utils::unwrap;
 use alloc::alloc::{Alloc, Global};
-use core::num::NonZeroUsize;
-use core::mem::ManuallyDrop;
+use core::convert::TryFrom;
+use core::mem::{needs_drop, size_of, ManuallyDrop};
+use core::num::{NonZeroU16, NonZeroU32, NonZeroUsize};
 
-#[derive(Clone, Copy)]
-struct Index(NonZeroUsize);
+mod generational;
+pub use self::generational::{GenerationalChunk, GenerationalHandle};
 
-impl Index {
-    pub fn new(x: usize) -> Self {
-        Self(NonZeroUsize::new(!x).expect("index overflow"))
-    }
+/// Index of a slot in a [`Chunk`]. A narrower index makes handles,
+/// and the links between nodes of a list, smaller, at the cost of
+/// limiting the number of slots.
+pub trait Index: Copy {
+    /// Panics if `x` does not fit.
+    fn new(x: usize) -> Self;
+    fn get(self) -> usize;
+}
+
+#[doc(hidden)]
+macro nonmax($name:ident, $nonzero:ty, $t:ty) {
+    /// Stores `!x`, so that `Option<Self>` is no larger than `Self`,
+    /// and the maximum value cannot be used.
+    #[derive(Clone, Copy)]
+    pub struct $name($nonzero);
+
+    impl Index for $name {
+        fn new(x: usize) -> Self {
+            let x = <$t>::try_from(x).ok().and_then(|x| <$nonzero>::new(!x));
+            Self(x.expect("index overflow"))
+        }
 
-    pub fn get(self) -> usize {
-        !self.0.get()
+        fn get(self) -> usize {
+            unwrap(usize::try_from(!self.0.get()))
+        }
     }
 }
 
+nonmax!(NonMaxU16, NonZeroU16, u16);
+nonmax!(NonMaxU32, NonZeroU32, u32);
+nonmax!(NonMaxUsize, NonZeroUsize, usize);
+
 #[derive(Clone, Copy)]
-pub struct Handle(Index);
+pub struct Handle<I: Index = NonMaxUsize>(I);
 
-impl pool::Handle for Handle {}
+impl<I: Index> pool::Handle for Handle<I> {}
 
-union Node<T> {
+union Node<T, I: Index> {
     data: ManuallyDrop<T>,
-    next: Option<Index>,
+    next: Option<I>,
 }
 
-pub struct Chunk<T, P: Policy = DefaultPolicy, A: Alloc = Global> {
+const BITS: usize = usize::wrapping_mul(size_of::<usize>(), 8);
+
+/// One bit for every slot, set if the slot holds an element. Kept
+/// in a `Block` of its own, from the allocator of the chunk.
+struct Bitmap<P: Policy, A: Alloc>(Block<usize, P, A>);
+
+impl<P: Policy, A: Alloc> Bitmap<P, A> {
+    /// A bitmap for at least `size` slots, all unset.
+    fn new(allocator: A, size: usize) -> Self {
+        let words = usize::wrapping_div(
+            usize::wrapping_add(size, usize::wrapping_sub(BITS, 1)),
+            BITS,
+        );
+        let mut bitmap = Self(Block::new(allocator, words));
+        bitmap.clear();
+        bitmap
+    }
+
+    fn position(index: usize) -> (usize, usize) {
+        (
+            usize::wrapping_div(index, BITS),
+            1 << usize::wrapping_rem(index, BITS),
+        )
+    }
+
+    fn word(&self, word: usize) -> Option<usize> {
+        if word < self.0.capacity() {
+            Some(*self.0.get(word))
+        } else {
+            None
+        }
+    }
+
+    fn clear_from(&mut self, word: usize) {
+        for i in word..self.0.capacity() {
+            self.0.write(i, 0);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.clear_from(0);
+    }
+
+    fn get(&self, index: usize) -> bool {
+        let (word, mask) = Self::position(index);
+        self.word(word).map_or(false, |w| (w & mask) != 0)
+    }
+
+    fn set(&mut self, index: usize) {
+        let (word, mask) = Self::position(index);
+        while word >= self.0.capacity() {
+            let capacity = self.0.capacity();
+            assert!(self.0.grow(0) > 0, "bitmap is full");
+            self.clear_from(capacity);
+        }
+        *self.0.get_mut(word) |= mask;
+    }
+
+    fn unset(&mut self, index: usize) {
+        let (word, mask) = Self::position(index);
+        *self.0.get_mut(word) &= !mask;
+    }
+
+    /// The first set bit at or after `index`.
+    fn next(&self, index: usize) -> Option<usize> {
+        let (word, mask) = Self::position(index);
+        // clear the bits before `index`
+        let mut bits = self.word(word)? & !usize::wrapping_sub(mask, 1);
+        let mut word = word;
+        while bits == 0 {
+            word = usize::wrapping_add(word, 1);
+            bits = self.word(word)?;
+        }
+        Some(usize::wrapping_add(
+            usize::wrapping_mul(word, BITS),
+            unwrap(usize::try_from(bits.trailing_zeros())),
+        ))
+    }
+}
+
+/// Pool storing elements in a single `Block`, reusing the slots of
+/// removed elements. Elements not removed are dropped together with
+/// the chunk.
+pub struct Chunk<T, P: Policy = DefaultPolicy, A: Alloc = Global, I: Index = NonMaxUsize> {
     size: usize,
-    next: Option<Index>,
-    data: Block<Node<T>, P, A>,
+    len: usize,
+    next: Option<I>,
+    live: Bitmap<P, A>,
+    data: Block<Node<T, I>, P, A>,
 }
 
-impl<T, P: Policy, A: Alloc> Chunk<T, P, A> {
+impl<T, P: Policy, A: Alloc + Clone, I: Index> Chunk<T, P, A, I> {
     pub fn new(allocator: A, capacity: usize) -> Self {
+        let data = Block::new(allocator.clone(), capacity);
         Self {
             size: 0,
+            len: 0,
             next: None,
-            data: Block::new(allocator, capacity),
+            live: Bitmap::new(allocator, data.capacity()),
+            data,
         }
     }
 }
 
-impl<T, P: Policy, A: Alloc + Default> Chunk<T, P, A> {
+impl<T, P: Policy, A: Alloc, I: Index> Chunk<T, P, A, I> {
+    /// Number of elements in the chunk.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether `handle` refers to an element which has not been
+    /// removed. A removed slot may be reused by a later `add`, after
+    /// which old handles to it are live again.
+    pub fn is_live(&self, handle: Handle<I>) -> bool {
+        self.live.get(handle.0.get())
+    }
+
+    /// Catch stale handles passed to `get` and `get_mut` in debug
+    /// builds only, since they are on the hot path of every linked
+    /// structure built on a chunk.
+    fn check(&self, handle: Handle<I>) -> usize {
+        let index = handle.0.get();
+        debug_assert!(self.live.get(index), "handle not live");
+        index
+    }
+
+    /// Drop every element, keeping the capacity.
+    pub fn clear(&mut self) {
+        if needs_drop::<T>() {
+            for index in 0..self.size {
+                if self.live.get(index) {
+                    unsafe { ManuallyDrop::drop(&mut self.data.get_mut(index).data) };
+                }
+            }
+        }
+
+        self.size = 0;
+        self.len = 0;
+        self.next = None;
+        self.live.clear();
+    }
+}
+
+impl<T, P: Policy, A: Alloc + Clone + Default, I: Index> Chunk<T, P, A, I> {
     pub fn new_with_capacity(capacity: usize) -> Self {
         Self::new(Default::default(), capacity)
     }
 }
 
-impl<T, P: Policy, A: Alloc> Pool for Chunk<T, P, A> {
-    type Handle = Handle;
+impl<T, P: Policy, A: Alloc, I: Index> Drop for Chunk<T, P, A, I> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, P: Policy, A: Alloc, I: Index> Pool for Chunk<T, P, A, I> {
+    type Handle = Handle<I>;
     type Elem = T;
 
-    fn get(&self, handle: Handle) -> &T {
-        unsafe { &self.data.get(handle.0.get()).data }
+    fn get(&self, handle: Handle<I>) -> &T {
+        let index = self.check(handle);
+        unsafe { &self.data.get(index).data }
     }
 
-    fn get_mut(&mut self, handle: Handle) -> &mut T {
-        unsafe { &mut self.data.get_mut(handle.0.get()).data }
+    fn get_mut(&mut self, handle: Handle<I>) -> &mut T {
+        let index = self.check(handle);
+        unsafe { &mut self.data.get_mut(index).data }
     }
 
-    fn add(&mut self, item: T) -> Handle {
-        let index = match self.next {
+    fn add(&mut self, item: T) -> Handle<I> {
+        let index = self.next.map_or(self.size, |next| next.get());
+        // convert first, so that an overflow leaves the chunk intact
+        let handle = Handle(I::new(index));
+
+        match self.next {
             None => {
-                let size = self.size;
-                self.size = usize::wrapping_add(self.size, 1);
-                if size == self.data.capacity() {
-                    assert!(self.data.grow(0) > 0);
+                if self.size == self.data.capacity() {
+                    assert!(
+                        self.data.grow(0) > 0,
+                        "chunk is full, its capacity policy does not grow"
+                    );
                 }
-                size
+                self.size = usize::wrapping_add(self.size, 1);
             }
-            Some(handle) => {
-                self.next = unsafe { self.data.get(handle.get()).next };
-                handle.get()
+            Some(next) => {
+                self.next = unsafe { self.data.get(next.get()).next };
             }
-        };
+        }
 
-        self.data.write(index, Node { data: ManuallyDrop::new(item) });
-        Handle(Index::new(index))
+        self.data.write(
+            index,
+            Node {
+                data: ManuallyDrop::new(item),
+            },
+        );
+        self.live.set(index);
+        self.len = usize::wrapping_add(self.len, 1);
+        handle
     }
 
-    fn remove(&mut self, handle: Handle) -> T {
+    fn remove(&mut self, handle: Handle<I>) -> T {
+        // always checked, removing twice would drop the element twice
         let index = handle.0.get();
+        assert!(self.live.get(index), "handle not live");
         let node = self.data.read(index);
         self.data.write(index, Node { next: self.next });
         self.next = Some(handle.0);
+        self.live.unset(index);
+        self.len = usize::wrapping_sub(self.len, 1);
         ManuallyDrop::into_inner(unsafe { node.data })
     }
 }
+
+impl<T, P: Policy, A: Alloc, I: Index> IterablePool for Chunk<T, P, A, I> {
+    fn next_handle(&self, handle: Option<Handle<I>>) -> Option<Handle<I>> {
+        let start = handle.map_or(0, |h| usize::wrapping_add(h.0.get(), 1));
+        self.live.next(start).map(|index| Handle(I::new(index)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Chunk;
+    use crate::capacity::FixedPolicy;
+    use crate::pool;
+
+    #[test]
+    fn test_occupancy() {
+        let mut c: Chunk<usize> = Chunk::new_with_capacity(0);
+        let handles: alloc::vec::Vec<_> = (0..100).map(|i| pool::add(&mut c, i)).collect();
+        assert!(c.len() == 100);
+
+        for &h in handles.iter().step_by(2) {
+            pool::remove(&mut c, h);
+        }
+        assert!(c.len() == 50);
+        assert!(!c.is_live(handles[0]));
+        assert!(c.is_live(handles[1]));
+
+        let h = pool::add(&mut c, 1000);
+        assert!(c.is_live(h));
+        assert!(*pool::get(&c, h) == 1000);
+
+        c.clear();
+        assert!(c.is_empty());
+        assert!(!c.is_live(h));
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut c: Chunk<usize> = Chunk::new_with_capacity(0);
+        let handles: alloc::vec::Vec<_> = (0..100).map(|i| pool::add(&mut c, i)).collect();
+        for &h in handles.iter().step_by(2) {
+            pool::remove(&mut c, h);
+        }
+        pool::add(&mut c, 1000);
+
+        let odd: alloc::vec::Vec<usize> = pool::iter(&c).cloned().collect();
+        assert!(odd.len() == 51);
+        assert!(odd.iter().filter(|&&x| x != 1000).all(|&x| x % 2 == 1));
+
+        for x in pool::iter_mut(&mut c) {
+            *x += 1;
+        }
+        let even: alloc::vec::Vec<usize> = pool::iter(&c).cloned().collect();
+        assert!(even.iter().filter(|&&x| x != 1001).all(|&x| x % 2 == 0));
+
+        c.clear();
+        assert!(pool::handles(&c).next().is_none());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "handle not live")]
+    fn test_stale_handle() {
+        let mut c: Chunk<usize> = Chunk::new_with_capacity(0);
+        let h = pool::add(&mut c, 1);
+        pool::remove(&mut c, h);
+        pool::get(&c, h);
+    }
+
+    #[test]
+    #[should_panic(expected = "handle not live")]
+    fn test_remove_stale_handle() {
+        let mut c: Chunk<usize> = Chunk::new_with_capacity(0);
+        let h = pool::add(&mut c, 1);
+        pool::remove(&mut c, h);
+        pool::remove(&mut c, h);
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk is full")]
+    fn test_fixed_full() {
+        let mut c: Chunk<usize, FixedPolicy> = Chunk::new_with_capacity(2);
+        for i in 0..3 {
+            pool::add(&mut c, i);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use super::{Index, Node, NonMaxUsize};
+use crate::block::Block;
+use crate::capacity::{DefaultPolicy, Policy};
+use crate::pool::{self, IterablePool, Pool};
+use alloc::alloc::{Alloc, Global};
+use core::mem::{needs_drop, replace, ManuallyDrop};
+
+/// Handle to an element of a [`GenerationalChunk`], which is stale
+/// once the element is removed, even if its slot is reused.
+#[derive(Clone, Copy)]
+pub struct GenerationalHandle {
+    index: NonMaxUsize,
+    generation: u32,
+}
+
+impl pool::Handle for GenerationalHandle {}
+
+/// The generation of a slot is odd while it holds an element, and is
+/// bumped on every `add` and `remove`.
+struct Slot<T> {
+    generation: u32,
+    node: Node<T, NonMaxUsize>,
+}
+
+/// Same as [`Chunk`](super::Chunk), except that a stale handle is
+/// detected instead of silently referring to whatever element took
+/// its slot. `Pool::get` and `Pool::get_mut` panic on a stale handle,
+/// `try_get` and `try_get_mut` return `None`.
+///
+/// The generation wraps around after 2<sup>31</sup> reuses of a slot.
+pub struct GenerationalChunk<T, P: Policy = DefaultPolicy, A: Alloc = Global> {
+    size: usize,
+    len: usize,
+    next: Option<NonMaxUsize>,
+    data: Block<Slot<T>, P, A>,
+}
+
+impl<T, P: Policy, A: Alloc> GenerationalChunk<T, P, A> {
+    pub fn new(allocator: A, capacity: usize) -> Self {
+        Self {
+            size: 0,
+            len: 0,
+            next: None,
+            data: Block::new(allocator, capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn slot(&self, handle: GenerationalHandle) -> Option<&Slot<T>> {
+        let index = handle.index.get();
+        if index >= self.size {
+            return None;
+        }
+        let slot = self.data.get(index);
+        if slot.generation == handle.generation {
+            Some(slot)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_live(&self, handle: GenerationalHandle) -> bool {
+        self.slot(handle).is_some()
+    }
+
+    pub fn try_get(&self, handle: GenerationalHandle) -> Option<&T> {
+        self.slot(handle).map(|slot| unsafe { &*slot.node.data })
+    }
+
+    pub fn try_get_mut(&mut self, handle: GenerationalHandle) -> Option<&mut T> {
+        if !self.is_live(handle) {
+            return None;
+        }
+        Some(unsafe { &mut self.data.get_mut(handle.index.get()).node.data })
+    }
+
+    /// Drop every element, keeping the capacity. Handles to them
+    /// stay stale.
+    pub fn clear(&mut self) {
+        let mut next = None;
+        for index in (0..self.size).rev() {
+            let slot = self.data.get_mut(index);
+            if (slot.generation & 1) == 1 {
+                if needs_drop::<T>() {
+                    unsafe { ManuallyDrop::drop(&mut slot.node.data) };
+                }
+                slot.generation = u32::wrapping_add(slot.generation, 1);
+            }
+            slot.node = Node { next };
+            next = Some(NonMaxUsize::new(index));
+        }
+        self.len = 0;
+        self.next = next;
+    }
+}
+
+impl<T, P: Policy, A: Alloc + Default> GenerationalChunk<T, P, A> {
+    pub fn new_with_capacity(capacity: usize) -> Self {
+        Self::new(Default::default(), capacity)
+    }
+}
+
+impl<T, P: Policy, A: Alloc> Drop for GenerationalChunk<T, P, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+fn stale(handle: GenerationalHandle) -> ! {
+    panic!(
+        "stale handle to slot {} at generation {}",
+        handle.index.get(),
+        handle.generation
+    )
+}
+
+impl<T, P: Policy, A: Alloc> Pool for GenerationalChunk<T, P, A> {
+    type Handle = GenerationalHandle;
+    type Elem = T;
+
+    fn get(&self, handle: GenerationalHandle) -> &T {
+        self.try_get(handle).unwrap_or_else(|| stale(handle))
+    }
+
+    fn get_mut(&mut self, handle: GenerationalHandle) -> &mut T {
+        match self.try_get_mut(handle) {
+            Some(x) => x,
+            None => stale(handle),
+        }
+    }
+
+    fn add(&mut self, item: T) -> GenerationalHandle {
+        let node = Node {
+            data: ManuallyDrop::new(item),
+        };
+        let index = match self.next {
+            None => {
+                let size = self.size;
+                if size == self.data.capacity() {
+                    assert!(
+                        self.data.grow(0) > 0,
+                        "chunk is full, its capacity policy does not grow"
+                    );
+                }
+                self.data.write(
+                    size,
+                    Slot {
+                        generation: 0,
+                        node,
+                    },
+                );
+                // only count the slot once written, so that `clear`
+                // never reads past it if growing panics
+                self.size = usize::wrapping_add(size, 1);
+                size
+            }
+            Some(index) => {
+                let slot = self.data.get_mut(index.get());
+                self.next = unsafe { slot.node.next };
+                slot.node = node;
+                index.get()
+            }
+        };
+
+        let slot = self.data.get_mut(index);
+        slot.generation = u32::wrapping_add(slot.generation, 1);
+        self.len = usize::wrapping_add(self.len, 1);
+        GenerationalHandle {
+            index: NonMaxUsize::new(index),
+            generation: slot.generation,
+        }
+    }
+
+    fn remove(&mut self, handle: GenerationalHandle) -> T {
+        if !self.is_live(handle) {
+            stale(handle);
+        }
+
+        let next = self.next;
+        let slot = self.data.get_mut(handle.index.get());
+        let node = replace(&mut slot.node, Node { next });
+        slot.generation = u32::wrapping_add(slot.generation, 1);
+        self.next = Some(handle.index);
+        self.len = usize::wrapping_sub(self.len, 1);
+        ManuallyDrop::into_inner(unsafe { node.data })
+    }
+}
+
+impl<T, P: Policy, A: Alloc> IterablePool for GenerationalChunk<T, P, A> {
+    fn next_handle(&self, handle: Option<GenerationalHandle>) -> Option<GenerationalHandle> {
+        let start = handle.map_or(0, |h| usize::wrapping_add(h.index.get(), 1));
+        (start..self.size).find_map(|index| {
+            let generation = self.data.get(index).generation;
+            if (generation & 1) == 1 {
+                Some(GenerationalHandle {
+                    index: NonMaxUsize::new(index),
+                    generation,
+                })
+            } else {
+                None
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GenerationalChunk;
+    use crate::capacity::FixedPolicy;
+    use crate::pool;
+
+    #[test]
+    #[should_panic(expected = "chunk is full")]
+    fn test_fixed_full() {
+        let mut c: GenerationalChunk<usize, FixedPolicy> = GenerationalChunk::new_with_capacity(2);
+        for i in 0..3 {
+            pool::add(&mut c, i);
+        }
+    }
+}
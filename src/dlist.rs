@@ -129,6 +129,12 @@ impl<T, H: Handle, P: Pool<Elem = Node<H, T>, Handle = H>> DoublyLinkedList<T, H
     }
 }
 
+impl<T, H: Handle, P: Pool<Elem = Node<H, T>, Handle = H>> Drop for DoublyLinkedList<T, H, P> {
+    fn drop(&mut self) {
+        while Deque::pop_front(self).is_some() {}
+    }
+}
+
 impl<T, H: Handle, P: Pool<Elem = Node<H, T>, Handle = H>> Deque for DoublyLinkedList<T, H, P> {
     type Elem = T;
 
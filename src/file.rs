@@ -1,5 +1,6 @@
 use crate::io;
 use crate::libc;
+use crate::utils::unwrap;
 use core::convert::TryFrom;
 use core::iter::Iterator;
 use core::ptr;
@@ -13,7 +14,10 @@ fn read(fd: i32, buf: *mut u8, count: usize) -> Result<*mut u8, libc::Error> {
                 }
             }
         } else {
-            return libc::get_error();
+            match libc::get_error() {
+                Err(libc::EINTR) => return read(fd, buf, count),
+                e => return e,
+            }
         }
     }
 
@@ -27,7 +31,10 @@ fn write(fd: i32, buf: *const u8, count: usize) -> Result<(), libc::Error> {
                 return write(fd, unsafe { buf.add(size) }, remain);
             }
         } else {
-            return libc::get_error();
+            match libc::get_error() {
+                Err(libc::EINTR) => return write(fd, buf, count),
+                e => return e,
+            }
         }
     }
 
@@ -57,25 +64,34 @@ impl Source {
     }
 }
 
-impl Iterator for Source {
-    type Item = u8;
-
-    fn next(&mut self) -> Option<u8> {
+impl io::TrySource for Source {
+    fn try_next(&mut self) -> Result<Option<u8>, libc::Error> {
         if (self.current == self.end) && (self.end == unsafe { self.buffer.add(self.capacity) }) {
+            // keep the buffer exhausted if reading fails, so that the
+            // next call reads again instead of yielding stale bytes
+            let end = read(self.fd, self.buffer, self.capacity)?;
             self.current = self.buffer;
-            self.end = read(self.fd, self.buffer, self.capacity).expect("read failed");
+            self.end = end;
         }
 
         if self.current < self.end {
             let c = unsafe { ptr::read(self.current) };
             self.current = unsafe { self.current.add(1) };
-            Some(c)
+            Ok(Some(c))
         } else {
-            None
+            Ok(None)
         }
     }
 }
 
+impl Iterator for Source {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        io::TrySource::try_next(self).expect("read failed")
+    }
+}
+
 pub struct Sink {
     fd: i32,
     current: *mut u8,
@@ -96,22 +112,40 @@ impl Sink {
     }
 }
 
-impl io::Sink for Sink {
-    fn write(&mut self, c: u8) {
+impl io::TrySink for Sink {
+    fn try_write(&mut self, c: u8) -> Result<(), libc::Error> {
         if self.current == unsafe { self.buffer.add(self.capacity) } {
-            write(self.fd, self.buffer, self.capacity).expect("write failed");
-            self.current = self.buffer;
+            io::TrySink::try_flush(self)?;
         }
 
         unsafe { ptr::write(self.current, c) };
         self.current = unsafe { self.current.add(1) };
+        Ok(())
+    }
+
+    fn try_flush(&mut self) -> Result<(), libc::Error> {
+        let size = unwrap(TryFrom::try_from(unsafe {
+            self.current.offset_from(self.buffer)
+        }));
+        self.current = self.buffer;
+        write(self.fd, self.buffer, size)
+    }
+}
+
+impl io::Sink for Sink {
+    fn write(&mut self, c: u8) {
+        match io::TrySink::try_write(self, c) {
+            // the reader has gone away, discard the rest of output
+            Ok(()) | Err(libc::EPIPE) => {}
+            Err(e) => panic!("write failed: {}", e),
+        }
     }
 }
 
 impl Drop for Sink {
     fn drop(&mut self) {
-        if let Ok(size) = TryFrom::try_from(unsafe { self.current.offset_from(self.buffer) }) {
-            write(self.fd, self.buffer, size).expect("write failed");
-        }
+        // errors cannot be reported from a destructor, call
+        // `TrySink::try_flush` before dropping to handle them
+        io::TrySink::try_flush(self).ok();
     }
 }
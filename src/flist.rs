@@ -42,6 +42,38 @@ impl<T, H: Handle, P: Pool<Elem = Node<H, T>, Handle = H> + Default> Default
     }
 }
 
+impl<T, H: Handle, P: Pool<Elem = Node<H, T>, Handle = H>> SinglyLinkedList<T, H, P> {
+    pub fn iter(&self) -> Iter<T, H, P> {
+        Iter {
+            pool: &self.pool,
+            handle: self.sentinel,
+            _data: PhantomData,
+        }
+    }
+}
+
+pub struct Iter<'a, T, H: Handle, P: Pool<Elem = Node<H, T>, Handle = H>> {
+    pool: &'a P,
+    handle: Option<H>,
+    _data: PhantomData<T>,
+}
+
+impl<'a, T: 'a, H: Handle, P: Pool<Elem = Node<H, T>, Handle = H>> Iterator for Iter<'a, T, H, P> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = pool::get(self.pool, self.handle?);
+        self.handle = node.next;
+        Some(&node.data)
+    }
+}
+
+impl<T, H: Handle, P: Pool<Elem = Node<H, T>, Handle = H>> Drop for SinglyLinkedList<T, H, P> {
+    fn drop(&mut self) {
+        while Stack::pop(self).is_some() {}
+    }
+}
+
 impl<T, H: Handle, P: Pool<Elem = Node<H, T>, Handle = H>> Stack for SinglyLinkedList<T, H, P> {
     type Elem = T;
 
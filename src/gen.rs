@@ -0,0 +1,344 @@
+//! Test data generators, similar to those in
+//! [testlib](https://github.com/MikeMirzayanov/testlib).
+//!
+//! Everything is generated from a [`random::Rng`], so the same seed
+//! always gives the same test.
+//!
+//! ```
+//! # use porus::prelude::*;
+//! use porus::gen;
+//! use porus::random::Xoshiro256StarStar;
+//!
+//! let rng = &mut Xoshiro256StarStar::new(1);
+//! let sink: &mut Vec<u8> = &mut Vec::new();
+//!
+//! let n = 5;
+//! let edges = gen::connected_graph(rng, n, 7);
+//! fmt::fwrite(sink, f!("{:d} {:d}\n", n, edges.len()));
+//! gen::write_edges(sink, &edges);
+//!
+//! let a = gen::distinct(rng, n, 1, 1_000_000_000);
+//! gen::write_array(sink, &a);
+//! ```
+
+use crate::fmt::{f, fwrite, fwrite_str, join, Int};
+use crate::io::Sink;
+use crate::list::{get, get_mut};
+use crate::random::{self, permutation, range, shuffle, Rng};
+use crate::utils::unwrap;
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// `n` numbers in `low..high`.
+pub fn array<R: Rng>(rng: &mut R, n: usize, low: i64, high: i64) -> Vec<i64> {
+    (0..n).map(|_| range(rng, low, high)).collect()
+}
+
+/// `n` distinct numbers in `low..high`, in random order.
+pub fn distinct<R: Rng>(rng: &mut R, n: usize, low: i64, high: i64) -> Vec<i64> {
+    let count = i128::wrapping_sub(From::from(high), From::from(low));
+    let size = i128::from(unwrap::<u64, _>(TryFrom::try_from(n)));
+    assert!(size <= count, "not enough distinct numbers");
+
+    if count <= i128::wrapping_mul(size, 2) {
+        let mut all: Vec<i64> = (low..high).collect();
+        random::sample(rng, &mut all, n);
+        all.truncate(n);
+        return all;
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut a = Vec::with_capacity(n);
+    while a.len() < n {
+        let x = range(rng, low, high);
+        if seen.insert(x) {
+            a.push(x);
+        }
+    }
+    a
+}
+
+/// `n` numbers in `low..high`, in non-decreasing order.
+pub fn sorted<R: Rng>(rng: &mut R, n: usize, low: i64, high: i64) -> Vec<i64> {
+    let mut a = array(rng, n, low, high);
+    a.sort_unstable();
+    a
+}
+
+/// `n` numbers in `low..high` whose sum is at most `max_sum`.
+pub fn bounded_sum<R: Rng>(rng: &mut R, n: usize, low: i64, high: i64, max_sum: i64) -> Vec<i64> {
+    assert!(low < high, "empty range");
+    let size = i64::try_from(n).expect("overflow");
+    let mut remain = i64::checked_mul(low, size)
+        .and_then(|min_sum| i64::checked_sub(max_sum, min_sum))
+        .expect("overflow");
+    assert!(remain >= 0, "max_sum too small");
+
+    let mut a: Vec<i64> = (0..n)
+        .map(|_| {
+            let bound = i64::min(i64::wrapping_sub(high, low), i64::wrapping_add(remain, 1));
+            let extra = range(rng, 0, bound);
+            remain = i64::wrapping_sub(remain, extra);
+            i64::wrapping_add(low, extra)
+        })
+        .collect();
+    shuffle(rng, &mut a);
+    a
+}
+
+/// A string of length `n` over `alphabet`.
+pub fn string<R: Rng>(rng: &mut R, n: usize, alphabet: &[u8]) -> Vec<u8> {
+    assert!(!alphabet.is_empty(), "empty alphabet");
+    (0..n)
+        .map(|_| unwrap(alphabet.get(range(rng, 0, alphabet.len()))))
+        .copied()
+        .collect()
+}
+
+fn relabel<R: Rng>(rng: &mut R, n: usize, edges: &mut Vec<(usize, usize)>) {
+    let label = permutation(rng, n);
+    for e in edges.iter_mut() {
+        *e = (*get(&label, e.0), *get(&label, e.1));
+        if random::bool(rng) {
+            *e = (e.1, e.0);
+        }
+    }
+    shuffle(rng, edges);
+}
+
+/// A tree on vertices `0..n`, uniformly chosen among all labelled
+/// trees by decoding a random Prüfer sequence.
+pub fn tree<R: Rng>(rng: &mut R, n: usize) -> Vec<(usize, usize)> {
+    if n < 2 {
+        return Vec::new();
+    }
+
+    let code: Vec<usize> = (2..n).map(|_| range(rng, 0, n)).collect();
+    let mut degree = vec![1_usize; n];
+    for &v in &code {
+        let d = get_mut(&mut degree, v);
+        *d = usize::wrapping_add(*d, 1);
+    }
+
+    let mut leaves: BTreeSet<usize> = (0..n).filter(|&v| *get(&degree, v) == 1).collect();
+    let mut edges = Vec::with_capacity(usize::wrapping_sub(n, 1));
+    for &v in &code {
+        let leaf = *leaves.iter().next().expect("no leaf");
+        leaves.remove(&leaf);
+        edges.push((leaf, v));
+        let d = get_mut(&mut degree, v);
+        *d = usize::wrapping_sub(*d, 1);
+        if *d == 1 {
+            leaves.insert(v);
+        }
+    }
+
+    let mut last = leaves.into_iter();
+    let u = last.next().expect("no leaf");
+    let v = last.next().expect("no leaf");
+    edges.push((u, v));
+    shuffle(rng, &mut edges);
+    edges
+}
+
+/// A path on vertices `0..n`, in random order.
+pub fn path<R: Rng>(rng: &mut R, n: usize) -> Vec<(usize, usize)> {
+    let mut edges = (1..n).map(|i| (usize::wrapping_sub(i, 1), i)).collect();
+    relabel(rng, n, &mut edges);
+    edges
+}
+
+/// A star on vertices `0..n` with a random center.
+pub fn star<R: Rng>(rng: &mut R, n: usize) -> Vec<(usize, usize)> {
+    let mut edges = (1..n).map(|i| (0, i)).collect();
+    relabel(rng, n, &mut edges);
+    edges
+}
+
+/// A caterpillar on vertices `0..n`, i.e. a path of `spine` vertices
+/// with every other vertex attached to one of them.
+pub fn caterpillar<R: Rng>(rng: &mut R, n: usize, spine: usize) -> Vec<(usize, usize)> {
+    assert!(0 < spine && spine <= n, "invalid spine length");
+    let mut edges: Vec<(usize, usize)> =
+        (1..spine).map(|i| (usize::wrapping_sub(i, 1), i)).collect();
+    edges.extend((spine..n).map(|i| (range(rng, 0, spine), i)));
+    relabel(rng, n, &mut edges);
+    edges
+}
+
+fn max_edges(n: usize) -> usize {
+    usize::checked_mul(n, usize::saturating_sub(n, 1))
+        .map_or(usize::max_value(), |x| usize::wrapping_div(x, 2))
+}
+
+/// A connected simple undirected graph on vertices `0..n` with `m`
+/// edges, i.e. a random tree plus random edges without loops or
+/// multiple edges.
+pub fn connected_graph<R: Rng>(rng: &mut R, n: usize, m: usize) -> Vec<(usize, usize)> {
+    assert!(usize::saturating_sub(n, 1) <= m, "too few edges");
+    assert!(m <= max_edges(n), "too many edges");
+
+    let mut edges = tree(rng, n);
+    let mut seen: BTreeSet<(usize, usize)> = edges
+        .iter()
+        .map(|&(u, v)| (usize::min(u, v), usize::max(u, v)))
+        .collect();
+    while edges.len() < m {
+        let u = range(rng, 0, n);
+        let v = range(rng, 0, n);
+        if u != v && seen.insert((usize::min(u, v), usize::max(u, v))) {
+            edges.push((u, v));
+        }
+    }
+    shuffle(rng, &mut edges);
+    edges
+}
+
+/// A directed acyclic graph on vertices `0..n` with `m` edges, no
+/// multiple edges, and a random topological order.
+pub fn dag<R: Rng>(rng: &mut R, n: usize, m: usize) -> Vec<(usize, usize)> {
+    assert!(m <= max_edges(n), "too many edges");
+    let order = permutation(rng, n);
+
+    let mut seen = BTreeSet::new();
+    let mut edges = Vec::with_capacity(m);
+    while edges.len() < m {
+        let u = range(rng, 0, n);
+        let v = range(rng, 0, n);
+        if u < v && seen.insert((u, v)) {
+            edges.push((*get(&order, u), *get(&order, v)));
+        }
+    }
+    edges
+}
+
+/// A grid of `h` rows and `w` columns over `alphabet`.
+pub fn grid<R: Rng>(rng: &mut R, h: usize, w: usize, alphabet: &[u8]) -> Vec<Vec<u8>> {
+    (0..h).map(|_| string(rng, w, alphabet)).collect()
+}
+
+/// Write numbers separated by spaces, followed by a newline.
+pub fn write_array<S: Sink, T: Int + Copy>(s: &mut S, a: &[T]) {
+    fwrite(
+        s,
+        f!("{}\n", join(f!(" "), a.iter().map(|&x| f!("{:d}", x)))),
+    );
+}
+
+/// Write one edge per line, with vertices numbered from 1.
+pub fn write_edges<S: Sink>(s: &mut S, edges: &[(usize, usize)]) {
+    for &(u, v) in edges {
+        fwrite(
+            s,
+            f!(
+                "{:d} {:d}\n",
+                usize::wrapping_add(u, 1),
+                usize::wrapping_add(v, 1)
+            ),
+        );
+    }
+}
+
+/// Write one row per line.
+pub fn write_grid<S: Sink>(s: &mut S, grid: &[Vec<u8>]) {
+    for row in grid {
+        fwrite_str(s, row);
+        fwrite_str(s, "\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bounded_sum, caterpillar, connected_graph, dag, distinct, path, star, tree};
+    use crate::random::Xoshiro256StarStar;
+    use alloc::vec::Vec;
+
+    fn find(p: &mut Vec<usize>, x: usize) -> usize {
+        if p[x] == x {
+            x
+        } else {
+            let parent = p[x];
+            let r = find(p, parent);
+            p[x] = r;
+            r
+        }
+    }
+
+    fn is_tree(n: usize, edges: &[(usize, usize)]) -> bool {
+        let mut parent: Vec<usize> = (0..n).collect();
+        edges.len() + 1 == n
+            && edges.iter().all(|&(u, v)| {
+                let (ru, rv) = (find(&mut parent, u), find(&mut parent, v));
+                parent[ru] = rv;
+                ru != rv
+            })
+    }
+
+    #[test]
+    fn test_trees() {
+        let rng = &mut Xoshiro256StarStar::new(0);
+        for n in 1..20 {
+            assert!(is_tree(n, &tree(rng, n)));
+            assert!(is_tree(n, &path(rng, n)));
+            assert!(is_tree(n, &star(rng, n)));
+            assert!(is_tree(n, &caterpillar(rng, n, (n + 1) / 2)));
+        }
+    }
+
+    #[test]
+    fn test_connected_graph() {
+        let rng = &mut Xoshiro256StarStar::new(1);
+        let edges = connected_graph(rng, 10, 20);
+        assert!(edges.len() == 20);
+        let mut parent: Vec<usize> = (0..10).collect();
+        for &(u, v) in &edges {
+            assert!(u != v);
+            let (ru, rv) = (find(&mut parent, u), find(&mut parent, v));
+            parent[ru] = rv;
+        }
+        let root = find(&mut parent, 0);
+        assert!((0..10).all(|v| find(&mut parent, v) == root));
+    }
+
+    #[test]
+    fn test_dag() {
+        let rng = &mut Xoshiro256StarStar::new(2);
+        let edges = dag(rng, 8, 20);
+        let mut indegree = [0; 8];
+        for &(_, v) in &edges {
+            indegree[v] += 1;
+        }
+        let mut stack: Vec<usize> = (0..8).filter(|&v| indegree[v] == 0).collect();
+        let mut visited = 0;
+        while let Some(u) = stack.pop() {
+            visited += 1;
+            for &(_, v) in edges.iter().filter(|&&(x, _)| x == u) {
+                indegree[v] -= 1;
+                if indegree[v] == 0 {
+                    stack.push(v);
+                }
+            }
+        }
+        assert!(visited == 8);
+    }
+
+    #[test]
+    fn test_arrays() {
+        let rng = &mut Xoshiro256StarStar::new(3);
+        let mut a = distinct(rng, 10, -5, 5);
+        a.sort();
+        assert!(a == (-5..5).collect::<Vec<_>>());
+
+        let mut b = distinct(rng, 100, 0, 1_000_000);
+        b.sort();
+        b.dedup();
+        assert!(b.len() == 100);
+
+        let c = bounded_sum(rng, 100, 1, 1000, 500);
+        assert!(c.len() == 100);
+        assert!(c.iter().all(|&x| 1 <= x && x < 1000));
+        assert!(c.iter().sum::<i64>() <= 500);
+    }
+}
@@ -1,4 +1,6 @@
-use core::iter::Iterator;
+use crate::libc;
+use alloc::vec::Vec;
+use core::iter::{Chain, Iterator, Take};
 
 pub trait Source: Iterator<Item = u8> {}
 
@@ -75,6 +77,163 @@ impl<'a> From<&'a [u8]> for PeekableSource<Bytes<'a>> {
     }
 }
 
+pub fn chain<A: Source, B: Source>(a: A, b: B) -> Chain<A, B> {
+    Iterator::chain(a, b)
+}
+
+pub fn take<S: Source>(s: S, n: usize) -> Take<S> {
+    Iterator::take(s, n)
+}
+
 pub trait Sink {
     fn write(&mut self, c: u8);
 }
+
+pub trait TrySource {
+    fn try_next(&mut self) -> Result<Option<u8>, libc::Error>;
+}
+
+pub trait TrySink {
+    fn try_write(&mut self, c: u8) -> Result<(), libc::Error>;
+    fn try_flush(&mut self) -> Result<(), libc::Error>;
+}
+
+impl<'a, S: Sink + ?Sized> Sink for &'a mut S {
+    fn write(&mut self, c: u8) {
+        Sink::write(*self, c)
+    }
+}
+
+impl Sink for Vec<u8> {
+    fn write(&mut self, c: u8) {
+        self.push(c)
+    }
+}
+
+pub struct BytesMut<'a> {
+    s: &'a mut [u8],
+    size: usize,
+}
+
+impl<'a> BytesMut<'a> {
+    pub fn new(s: &'a mut [u8]) -> Self {
+        Self { s, size: 0 }
+    }
+
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<'a> AsRef<[u8]> for BytesMut<'a> {
+    fn as_ref(&self) -> &[u8] {
+        &self.s[..self.size]
+    }
+}
+
+impl<'a> Sink for BytesMut<'a> {
+    fn write(&mut self, c: u8) {
+        *self.s.get_mut(self.size).expect("buffer full") = c;
+        self.size = usize::wrapping_add(self.size, 1);
+    }
+}
+
+#[derive(Default)]
+pub struct Null;
+
+impl Sink for Null {
+    fn write(&mut self, _c: u8) {}
+}
+
+#[derive(Default)]
+pub struct Counter {
+    count: usize,
+}
+
+impl Counter {
+    pub const fn new() -> Self {
+        Self { count: 0 }
+    }
+
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Sink for Counter {
+    fn write(&mut self, _c: u8) {
+        self.count = usize::wrapping_add(self.count, 1);
+    }
+}
+
+pub struct Tee<A: Sink, B: Sink> {
+    a: A,
+    b: B,
+}
+
+impl<A: Sink, B: Sink> Tee<A, B> {
+    pub const fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+impl<A: Sink, B: Sink> Sink for Tee<A, B> {
+    fn write(&mut self, c: u8) {
+        Sink::write(&mut self.a, c);
+        Sink::write(&mut self.b, c);
+    }
+}
+
+pub fn tee<A: Sink, B: Sink>(a: A, b: B) -> Tee<A, B> {
+    Tee::new(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chain, take, tee, Bytes, BytesMut, Counter, Sink};
+    use crate::fmt::fwrite_str;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_counter() {
+        let mut counter = Counter::new();
+        fwrite_str(&mut counter, b"hello");
+        assert!(counter.count() == 5);
+    }
+
+    #[test]
+    fn test_tee() {
+        let mut v: Vec<u8> = Vec::new();
+        let mut counter = Counter::new();
+        fwrite_str(&mut tee(&mut v, &mut counter), b"abc");
+        assert!(v.as_slice() == b"abc");
+        assert!(counter.count() == 3);
+    }
+
+    #[test]
+    fn test_bytes_mut() {
+        let buf = &mut [0; 4];
+        let mut sink = BytesMut::new(buf);
+        Sink::write(&mut sink, b'x');
+        Sink::write(&mut sink, b'y');
+        assert!(sink.as_ref() == b"xy");
+    }
+
+    #[test]
+    #[should_panic(expected = "buffer full")]
+    fn test_bytes_mut_full() {
+        let buf = &mut [0; 1];
+        let mut sink = BytesMut::new(buf);
+        fwrite_str(&mut sink, b"xy");
+    }
+
+    #[test]
+    fn test_chain_take() {
+        let source = chain(Bytes::new(b"ab"), take(Bytes::new(b"cdef"), 2));
+        assert!(source.eq(Bytes::new(b"abcd")));
+    }
+}
@@ -0,0 +1,246 @@
+//! Runtime support for solutions submitted to
+//! [LeetCode](https://leetcode.com/)
+//!
+//! LeetCode calls C solutions with raw pointers, passing the length
+//! of each array in an extra argument and expecting arrays returned
+//! to be allocated by `malloc`, with their lengths stored through
+//! `returnSize` and `returnColumnSizes`. The
+//! [`#[leetcode]`](macro@leetcode) attribute generates such a C
+//! function for the `pub fn` of `impl Solution`, converting
+//! arguments and results with the functions of this module.
+//!
+//! Linked lists and binary trees are passed as `struct ListNode*` and
+//! `struct TreeNode*`, which are converted to
+//! [`SinglyLinkedList<i32>`](crate::flist::SinglyLinkedList) and
+//! [`Tree`](tree::Tree) respectively.
+//!
+//! To run solutions locally, use `prelude!(leetcode-local)` instead.
+//! Test cases are then read from standard input in the format shown
+//! on LeetCode, one argument after another, e.g.
+//!
+//! ```text
+//! [2,7,11,15]
+//! 9
+//! ```
+//!
+//! and the result of the `pub fn` of `impl Solution` is printed in
+//! the same format for each test case, followed by every argument
+//! taken by `&mut`, one per line. Since a test case does not tell
+//! which function it is for, `impl Solution` must have a single `pub
+//! fn`, other functions are private.
+//!
+//! ```ignore
+//! #[macro_use]
+//! extern crate porus;
+//! prelude!(leetcode);
+//!
+//! struct Solution;
+//!
+//! #[leetcode]
+//! impl Solution {
+//!     pub fn two_sum(nums: Vec<i32>, target: i32) -> Vec<i32> {
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+//! expands to
+//!
+//! ```ignore
+//! #[no_mangle]
+//! pub unsafe extern "C" fn twoSum(
+//!     nums: *const i32,
+//!     numsSize: i32,
+//!     target: i32,
+//!     returnSize: *mut i32,
+//! ) -> *mut i32 {
+//!     // ...
+//! }
+//! ```
+
+use crate::fmt::fwrite_str;
+use crate::libc;
+use crate::string::{String, StringBuffer};
+use crate::utils::unwrap;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::mem::size_of;
+use core::ptr::{copy_nonoverlapping, write};
+use core::slice::from_raw_parts;
+pub use porus_macros::leetcode;
+
+pub mod list;
+pub use self::list::{list_from_c, list_into_c, ListNode};
+
+pub mod tree;
+pub use self::tree::{tree_from_c, tree_into_c, Tree, TreeNode};
+
+mod text;
+pub use self::text::{has_next, literal, parse, read_arg, write_result, Literal, Parse};
+
+fn to_usize(size: i32) -> usize {
+    usize::try_from(size).expect("negative size")
+}
+
+fn to_i32(size: usize) -> i32 {
+    i32::try_from(size).expect("size overflow")
+}
+
+unsafe fn malloc_array<T>(n: usize) -> *mut T {
+    let size = unwrap(usize::checked_mul(size_of::<T>(), Ord::max(n, 1)));
+    let p = libc::malloc(size).cast();
+    assert!(!p.is_null(), "malloc failed");
+    p
+}
+
+unsafe fn malloc_node<T>() -> *mut T {
+    malloc_array(1)
+}
+
+unsafe fn c_str<'a>(p: *const u8) -> &'a [u8] {
+    let mut n = 0;
+    while *p.add(n) != 0 {
+        n = usize::wrapping_add(n, 1);
+    }
+    from_raw_parts(p, n)
+}
+
+pub fn string_from_bytes(s: &[u8]) -> String {
+    let mut buffer: StringBuffer = Default::default();
+    fwrite_str(&mut buffer, s);
+    From::from(buffer)
+}
+
+pub unsafe fn vec_from_c<T: Copy>(p: *const T, size: i32) -> Vec<T> {
+    from_raw_parts(p, to_usize(size)).to_vec()
+}
+
+/// Copy an array modified in place back. It may have been shrunk, e.g.
+/// when the solution returns the new length and the rest is ignored.
+pub unsafe fn vec_copy_to_c<T: Copy>(v: &[T], p: *mut T, size: i32) {
+    assert!(v.len() <= to_usize(size), "array grown");
+    copy_nonoverlapping(v.as_ptr(), p, v.len());
+}
+
+pub unsafe fn vec2_from_c<T: Copy>(
+    p: *const *const T,
+    size: i32,
+    col_size: *const i32,
+) -> Vec<Vec<T>> {
+    let rows = from_raw_parts(p, to_usize(size));
+    let cols = from_raw_parts(col_size, to_usize(size));
+    rows.iter()
+        .zip(cols.iter())
+        .map(|(&row, &col)| vec_from_c(row, col))
+        .collect()
+}
+
+pub unsafe fn string_from_c(p: *const u8) -> String {
+    string_from_bytes(c_str(p))
+}
+
+pub unsafe fn strings_from_c(p: *const *const u8, size: i32) -> Vec<String> {
+    from_raw_parts(p, to_usize(size))
+        .iter()
+        .map(|&s| string_from_c(s))
+        .collect()
+}
+
+pub unsafe fn vec_into_c<T: Copy>(v: Vec<T>, return_size: *mut i32) -> *mut T {
+    let p = malloc_array(v.len());
+    copy_nonoverlapping(v.as_ptr(), p, v.len());
+    write(return_size, to_i32(v.len()));
+    p
+}
+
+pub unsafe fn vec2_into_c<T: Copy>(
+    v: Vec<Vec<T>>,
+    return_size: *mut i32,
+    return_column_sizes: *mut *mut i32,
+) -> *mut *mut T {
+    let size = v.len();
+    let p = malloc_array(size);
+    let cols = malloc_array(size);
+    for (i, row) in v.into_iter().enumerate() {
+        write(p.add(i), vec_into_c(row, cols.add(i)));
+    }
+    write(return_column_sizes, cols);
+    write(return_size, to_i32(size));
+    p
+}
+
+pub unsafe fn string_into_c(s: &String) -> *mut u8 {
+    let bytes = AsRef::<[u8]>::as_ref(s);
+    let p = malloc_array(usize::wrapping_add(bytes.len(), 1));
+    copy_nonoverlapping(bytes.as_ptr(), p, bytes.len());
+    write(p.add(bytes.len()), 0);
+    p
+}
+
+pub unsafe fn strings_into_c(v: Vec<String>, return_size: *mut i32) -> *mut *mut u8 {
+    let p = malloc_array(v.len());
+    for (i, s) in v.iter().enumerate() {
+        write(p.add(i), string_into_c(s));
+    }
+    write(return_size, to_i32(v.len()));
+    p
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        string_from_c, string_into_c, vec2_from_c, vec2_into_c, vec_copy_to_c, vec_into_c,
+    };
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::slice::from_raw_parts;
+
+    #[test]
+    fn test_vec2_round_trip() {
+        let rows: [&[i32]; 2] = [&[1, 2, 3], &[4]];
+        let ptrs = [rows[0].as_ptr(), rows[1].as_ptr()];
+        let cols = [3, 1];
+        let v = unsafe { vec2_from_c(ptrs.as_ptr(), 2, cols.as_ptr()) };
+        assert!(v == vec![vec![1, 2, 3], vec![4]]);
+
+        let mut size = 0;
+        let mut col_sizes = core::ptr::null_mut();
+        let p = unsafe { vec2_into_c(v, &mut size, &mut col_sizes) };
+        assert!(size == 2);
+        let col_sizes = unsafe { from_raw_parts(col_sizes, 2) };
+        assert!(col_sizes == [3, 1]);
+        let row = unsafe { from_raw_parts(*p, 3) };
+        assert!(row == [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_vec_into_c() {
+        let mut size = 0;
+        let p = unsafe { vec_into_c(Vec::<i32>::new(), &mut size) };
+        assert!(!p.is_null());
+        assert!(size == 0);
+    }
+
+    #[test]
+    fn test_vec_copy_to_c_shrunk() {
+        let mut a = [3, 2, 2, 3];
+        let v = vec![2, 2];
+        unsafe { vec_copy_to_c(&v, a.as_mut_ptr(), 4) };
+        assert!(a[..2] == [2, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "array grown")]
+    fn test_vec_copy_to_c_grown() {
+        let mut a = [1];
+        unsafe { vec_copy_to_c(&[1, 2], a.as_mut_ptr(), 1) };
+    }
+
+    #[test]
+    fn test_string_round_trip() {
+        let s = unsafe { string_from_c(b"hello\0".as_ptr()) };
+        assert!(AsRef::<[u8]>::as_ref(&s) == b"hello");
+        let p = unsafe { string_into_c(&s) };
+        assert!(unsafe { from_raw_parts(p, 6) } == b"hello\0");
+    }
+}
@@ -0,0 +1,83 @@
+use super::malloc_node;
+use crate::flist::{Node, SinglyLinkedList};
+use crate::pool::{Handle, Pool};
+use crate::stack::{self, Stack};
+use alloc::vec::Vec;
+use core::ptr::{null_mut, write};
+
+#[repr(C)]
+pub struct ListNode {
+    pub val: i32,
+    pub next: *mut ListNode,
+}
+
+pub unsafe fn list_from_c<H: Handle, P: Pool<Elem = Node<H, i32>, Handle = H> + Default>(
+    mut head: *const ListNode,
+) -> SinglyLinkedList<i32, H, P> {
+    let mut v = Vec::new();
+    while !head.is_null() {
+        v.push((*head).val);
+        head = (*head).next;
+    }
+
+    let mut list = SinglyLinkedList::new();
+    for x in v.into_iter().rev() {
+        stack::push(&mut list, x);
+    }
+    list
+}
+
+pub unsafe fn list_into_c<H: Handle, P: Pool<Elem = Node<H, i32>, Handle = H>>(
+    mut list: SinglyLinkedList<i32, H, P>,
+) -> *mut ListNode {
+    let mut v = Vec::new();
+    while let Some(x) = Stack::pop(&mut list) {
+        v.push(x);
+    }
+
+    let mut head = null_mut();
+    for val in v.into_iter().rev() {
+        let node = malloc_node();
+        write(node, ListNode { val, next: head });
+        head = node;
+    }
+    head
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{list_from_c, list_into_c, ListNode};
+    use crate::flist::SinglyLinkedList;
+    use crate::stack;
+    use core::ptr::null_mut;
+
+    #[test]
+    fn test_list_round_trip() {
+        let c = &mut ListNode {
+            val: 3,
+            next: null_mut(),
+        };
+        let b = &mut ListNode { val: 2, next: c };
+        let a = &mut ListNode { val: 1, next: b };
+
+        let mut list: SinglyLinkedList<i32> = unsafe { list_from_c(a) };
+        assert!(*stack::top(&list) == 1);
+        stack::push(&mut list, 0);
+
+        let head = unsafe { list_into_c(list) };
+        let mut p = head;
+        for i in 0..4 {
+            assert!(!p.is_null());
+            assert!(unsafe { (*p).val } == i);
+            p = unsafe { (*p).next };
+        }
+        assert!(p.is_null());
+    }
+
+    #[test]
+    fn test_empty_list() {
+        let list: SinglyLinkedList<i32> = unsafe { list_from_c(null_mut()) };
+        assert!(stack::is_empty(&list));
+        assert!(unsafe { list_into_c(list) }.is_null());
+    }
+}
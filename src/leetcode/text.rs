@@ -0,0 +1,358 @@
+use super::tree::{Node as TreeNode, Tree};
+use crate::flist::{Node as ListNode, SinglyLinkedList};
+use crate::fmt::{self, fwrite_str, join};
+use crate::io::{PeekableSource, Sink, Source};
+use crate::pool::{Handle, Pool};
+use crate::scan::{fread, Consumer, Whitespace};
+use crate::stack;
+use crate::stdio;
+use crate::string::{String, StringBuffer};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+/// Values written in the format of LeetCode test cases, e.g.
+/// `[2,7,11,15]`, `"abc"`, `true` or `[1,null,2,3]`
+pub trait Literal: Sized {
+    fn parse<I: Source>(s: &mut PeekableSource<I>) -> Option<Self>;
+    fn format<S: Sink>(&self, s: &mut S);
+}
+
+fn expect<I: Source>(s: &mut PeekableSource<I>, word: &[u8]) -> bool {
+    for c in word {
+        if s.peek() != Some(c) {
+            return false;
+        }
+        s.consume();
+    }
+    true
+}
+
+#[doc(hidden)]
+macro int($t:ty) {
+    impl Literal for $t {
+        fn parse<I: Source>(s: &mut PeekableSource<I>) -> Option<Self> {
+            let mut x = Default::default();
+            if fread(s, &mut x) {
+                Some(x)
+            } else {
+                None
+            }
+        }
+
+        fn format<S: Sink>(&self, s: &mut S) {
+            fmt::Int::write(*self, s, 10, 1)
+        }
+    }
+}
+
+int!(i32);
+int!(i64);
+int!(u32);
+int!(u64);
+
+impl Literal for f64 {
+    fn parse<I: Source>(s: &mut PeekableSource<I>) -> Option<Self> {
+        let mut x = 0.0;
+        if fread(s, &mut x) {
+            Some(x)
+        } else {
+            None
+        }
+    }
+
+    fn format<S: Sink>(&self, s: &mut S) {
+        fmt::Float::write(*self, s, 5)
+    }
+}
+
+impl Literal for bool {
+    fn parse<I: Source>(s: &mut PeekableSource<I>) -> Option<Self> {
+        match s.peek().copied() {
+            Some(b't') if expect(s, b"true") => Some(true),
+            Some(b'f') if expect(s, b"false") => Some(false),
+            _ => None,
+        }
+    }
+
+    fn format<S: Sink>(&self, s: &mut S) {
+        fwrite_str(s, if *self { "true" } else { "false" })
+    }
+}
+
+impl Literal for String {
+    fn parse<I: Source>(s: &mut PeekableSource<I>) -> Option<Self> {
+        if !expect(s, b"\"") {
+            return None;
+        }
+
+        let mut buffer: StringBuffer = Default::default();
+        loop {
+            let c = *s.peek()?;
+            s.consume();
+            let c = match c {
+                b'"' => break,
+                b'\\' => {
+                    let e = *s.peek()?;
+                    s.consume();
+                    match e {
+                        b'n' => b'\n',
+                        b't' => b'\t',
+                        b'r' => b'\r',
+                        _ => e,
+                    }
+                }
+                _ => c,
+            };
+            Sink::write(&mut buffer, c);
+        }
+        Some(From::from(buffer))
+    }
+
+    fn format<S: Sink>(&self, s: &mut S) {
+        Sink::write(s, b'"');
+        for &c in AsRef::<[u8]>::as_ref(self) {
+            match c {
+                b'"' | b'\\' => {
+                    Sink::write(s, b'\\');
+                    Sink::write(s, c);
+                }
+                b'\n' => fwrite_str(s, "\\n"),
+                b'\t' => fwrite_str(s, "\\t"),
+                b'\r' => fwrite_str(s, "\\r"),
+                _ => Sink::write(s, c),
+            }
+        }
+        Sink::write(s, b'"');
+    }
+}
+
+impl<T: Literal> Literal for Option<T> {
+    fn parse<I: Source>(s: &mut PeekableSource<I>) -> Option<Self> {
+        match s.peek().copied() {
+            Some(b'n') if expect(s, b"null") => Some(None),
+            _ => T::parse(s).map(Some),
+        }
+    }
+
+    fn format<S: Sink>(&self, s: &mut S) {
+        match self {
+            None => fwrite_str(s, "null"),
+            Some(x) => x.format(s),
+        }
+    }
+}
+
+fn format_list<'a, S: Sink, T: 'a + Literal, It: Iterator<Item = &'a T>>(s: &mut S, it: It) {
+    Sink::write(s, b'[');
+    join(
+        |s: &mut S| Sink::write(s, b','),
+        it.map(|x| move |s: &mut S| x.format(s)),
+    )(s);
+    Sink::write(s, b']');
+}
+
+impl<T: Literal> Literal for Vec<T> {
+    fn parse<I: Source>(s: &mut PeekableSource<I>) -> Option<Self> {
+        if !expect(s, b"[") {
+            return None;
+        }
+
+        let mut v = Vec::new();
+        fread(s, Whitespace);
+        if expect(s, b"]") {
+            return Some(v);
+        }
+
+        loop {
+            fread(s, Whitespace);
+            v.push(T::parse(s)?);
+            fread(s, Whitespace);
+            if expect(s, b"]") {
+                return Some(v);
+            }
+            if !expect(s, b",") {
+                return None;
+            }
+        }
+    }
+
+    fn format<S: Sink>(&self, s: &mut S) {
+        format_list(s, self.iter())
+    }
+}
+
+impl<H: Handle, P: Pool<Elem = ListNode<H, i32>, Handle = H> + Default> Literal
+    for SinglyLinkedList<i32, H, P>
+{
+    fn parse<I: Source>(s: &mut PeekableSource<I>) -> Option<Self> {
+        let v: Vec<i32> = Literal::parse(s)?;
+        let mut list = Self::new();
+        for x in v.into_iter().rev() {
+            stack::push(&mut list, x);
+        }
+        Some(list)
+    }
+
+    fn format<S: Sink>(&self, s: &mut S) {
+        format_list(s, self.iter())
+    }
+}
+
+impl<H: Handle, P: Pool<Elem = TreeNode<H>, Handle = H> + Default> Literal for Tree<H, P> {
+    fn parse<I: Source>(s: &mut PeekableSource<I>) -> Option<Self> {
+        let v: Vec<Option<i32>> = Literal::parse(s)?;
+        let mut tree = Self::new();
+        let mut it = v.into_iter();
+        let root = match it.next() {
+            Some(Some(x)) => tree.add(x, None, None),
+            _ => return Some(tree),
+        };
+        tree.set_root(Some(root));
+
+        let mut queue = VecDeque::new();
+        queue.push_back(root);
+        while let Some(h) = queue.pop_front() {
+            if let Some(Some(x)) = it.next() {
+                let left = tree.add(x, None, None);
+                tree.set_left(h, Some(left));
+                queue.push_back(left);
+            }
+            if let Some(Some(x)) = it.next() {
+                let right = tree.add(x, None, None);
+                tree.set_right(h, Some(right));
+                queue.push_back(right);
+            }
+        }
+        Some(tree)
+    }
+
+    fn format<S: Sink>(&self, s: &mut S) {
+        let mut v: Vec<Option<i32>> = Vec::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(self.root());
+        while let Some(h) = queue.pop_front() {
+            v.push(h.map(|h| {
+                queue.push_back(self.left(h));
+                queue.push_back(self.right(h));
+                self.val(h)
+            }));
+        }
+        while let Some(None) = v.last() {
+            v.pop();
+        }
+        v.format(s)
+    }
+}
+
+pub struct Parse<'a, T: Literal>(pub &'a mut T);
+
+pub fn parse<T: Literal>(x: &mut T) -> Parse<T> {
+    Parse(x)
+}
+
+impl<'a, T: Literal> Consumer for Parse<'a, T> {
+    fn consume<I: Source>(self, s: &mut PeekableSource<I>) -> bool {
+        fread(s, Whitespace);
+        match T::parse(s) {
+            None => false,
+            Some(x) => {
+                *self.0 = x;
+                true
+            }
+        }
+    }
+}
+
+pub fn literal<'a, S: Sink, T: Literal>(x: &'a T) -> impl 'a + FnMut(&mut S) {
+    move |s: &mut S| x.format(s)
+}
+
+struct Arg<'a, T: Literal>(&'a mut Option<T>);
+
+impl<'a, T: Literal> Consumer for Arg<'a, T> {
+    fn consume<I: Source>(self, s: &mut PeekableSource<I>) -> bool {
+        fread(s, Whitespace);
+        *self.0 = T::parse(s);
+        self.0.is_some()
+    }
+}
+
+pub fn has_next() -> bool {
+    stdio::read(Whitespace);
+    !stdio::eof()
+}
+
+pub fn read_arg<T: Literal>() -> T {
+    let mut x = None;
+    stdio::read(Arg(&mut x));
+    x.expect("invalid test case")
+}
+
+pub fn write_result<T: Literal>(x: &T) {
+    stdio::writeln(literal(x));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Literal;
+    use crate::flist::SinglyLinkedList;
+    use crate::leetcode::Tree;
+    use crate::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn round_trip<T: Literal>(s: &[u8]) -> Vec<u8> {
+        let x: T = Literal::parse(&mut From::from(s)).expect("parse failed");
+        let mut v = Vec::new();
+        x.format(&mut v);
+        v
+    }
+
+    #[test]
+    fn test_array() {
+        let x: Vec<i32> = Literal::parse(&mut From::from(b"[2, 7,11 ,15]" as &_)).unwrap();
+        assert!(x == vec![2, 7, 11, 15]);
+        assert!(round_trip::<Vec<i32>>(b"[2, 7,11 ,15]") == b"[2,7,11,15]");
+        assert!(round_trip::<Vec<i32>>(b"[]") == b"[]");
+    }
+
+    #[test]
+    fn test_nested_array() {
+        assert!(round_trip::<Vec<Vec<i64>>>(b"[[1,-2],[],[3]]") == b"[[1,-2],[],[3]]");
+    }
+
+    #[test]
+    fn test_string() {
+        assert!(round_trip::<String>(br#""a\"b\\c""#) == br#""a\"b\\c""#);
+        assert!(round_trip::<Vec<String>>(br#"["eat","tea"]"#) == br#"["eat","tea"]"#);
+    }
+
+    #[test]
+    fn test_float() {
+        let parse = |s: &[u8]| -> Option<f64> { Literal::parse(&mut From::from(s)) };
+        assert!(parse(b"2.5").is_some());
+        assert!(parse(b"-.5").is_some());
+        assert!(parse(b"x").is_none());
+        assert!(parse(b"-").is_none());
+    }
+
+    #[test]
+    fn test_bool() {
+        assert!(round_trip::<bool>(b"true") == b"true");
+        assert!(round_trip::<bool>(b"false") == b"false");
+        let x: Option<bool> = Literal::parse(&mut From::from(b"fals" as &_));
+        assert!(x.is_none());
+    }
+
+    #[test]
+    fn test_list() {
+        assert!(round_trip::<SinglyLinkedList<i32>>(b"[1,2,3]") == b"[1,2,3]");
+    }
+
+    #[test]
+    fn test_tree() {
+        assert!(round_trip::<Tree>(b"[1,null,2,3]") == b"[1,null,2,3]");
+        assert!(round_trip::<Tree>(b"[3,9,20,null,null,15,7]") == b"[3,9,20,null,null,15,7]");
+        assert!(round_trip::<Tree>(b"[]") == b"[]");
+    }
+}
@@ -0,0 +1,250 @@
+use super::malloc_node;
+use crate::allocator;
+use crate::pool::{self, Handle, Pool};
+use crate::utils::unwrap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ptr::{null_mut, write};
+
+#[repr(C)]
+pub struct TreeNode {
+    pub val: i32,
+    pub left: *mut TreeNode,
+    pub right: *mut TreeNode,
+}
+
+pub struct Node<H: Handle> {
+    val: i32,
+    left: Option<H>,
+    right: Option<H>,
+}
+
+pub struct Tree<
+    H: Handle = allocator::Handle,
+    P: Pool<Elem = Node<H>, Handle = H> = allocator::Pool<Node<H>>,
+> {
+    pool: P,
+    root: Option<H>,
+}
+
+impl<H: Handle, P: Pool<Elem = Node<H>, Handle = H>> Tree<H, P> {
+    pub fn new_with_pool(pool: P) -> Self {
+        Self { pool, root: None }
+    }
+}
+
+impl<H: Handle, P: Pool<Elem = Node<H>, Handle = H> + Default> Tree<H, P> {
+    pub fn new() -> Self {
+        Self::new_with_pool(Default::default())
+    }
+}
+
+impl<H: Handle, P: Pool<Elem = Node<H>, Handle = H> + Default> Default for Tree<H, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: Handle, P: Pool<Elem = Node<H>, Handle = H>> Tree<H, P> {
+    pub fn root(&self) -> Option<H> {
+        self.root
+    }
+
+    pub fn set_root(&mut self, root: Option<H>) {
+        self.root = root;
+    }
+
+    pub fn add(&mut self, val: i32, left: Option<H>, right: Option<H>) -> H {
+        pool::add(&mut self.pool, Node { val, left, right })
+    }
+
+    pub fn val(&self, handle: H) -> i32 {
+        pool::get(&self.pool, handle).val
+    }
+
+    pub fn val_mut(&mut self, handle: H) -> &mut i32 {
+        &mut pool::get_mut(&mut self.pool, handle).val
+    }
+
+    pub fn left(&self, handle: H) -> Option<H> {
+        pool::get(&self.pool, handle).left
+    }
+
+    pub fn right(&self, handle: H) -> Option<H> {
+        pool::get(&self.pool, handle).right
+    }
+
+    pub fn set_left(&mut self, handle: H, left: Option<H>) {
+        pool::get_mut(&mut self.pool, handle).left = left;
+    }
+
+    pub fn set_right(&mut self, handle: H, right: Option<H>) {
+        pool::get_mut(&mut self.pool, handle).right = right;
+    }
+}
+
+impl<H: Handle, P: Pool<Elem = Node<H>, Handle = H>> Drop for Tree<H, P> {
+    fn drop(&mut self) {
+        let mut stack: Vec<H> = self.root.into_iter().collect();
+        while let Some(handle) = stack.pop() {
+            let node = pool::remove(&mut self.pool, handle);
+            stack.extend(node.left);
+            stack.extend(node.right);
+        }
+    }
+}
+
+/// Add the nodes below `root` children first, with an explicit
+/// stack like `Drop`, so that a deep tree does not overflow the call
+/// stack.
+unsafe fn add_from_c<H: Handle, P: Pool<Elem = Node<H>, Handle = H>>(
+    tree: &mut Tree<H, P>,
+    root: *const TreeNode,
+) -> Option<H> {
+    // a node is pushed again once its children are pushed, and added
+    // when popped the second time, after both children are added
+    let mut stack: Vec<(*const TreeNode, bool)> = vec![(root, false)];
+    let mut added: Vec<Option<H>> = Vec::new();
+    while let Some((node, children)) = stack.pop() {
+        if node.is_null() {
+            added.push(None);
+        } else if children {
+            let right = unwrap(added.pop());
+            let left = unwrap(added.pop());
+            added.push(Some(tree.add((*node).val, left, right)));
+        } else {
+            stack.push((node, true));
+            stack.push(((*node).right, false));
+            stack.push(((*node).left, false));
+        }
+    }
+    unwrap(added.pop())
+}
+
+pub unsafe fn tree_from_c<H: Handle, P: Pool<Elem = Node<H>, Handle = H> + Default>(
+    root: *const TreeNode,
+) -> Tree<H, P> {
+    let mut tree = Tree::new();
+    let root = add_from_c(&mut tree, root);
+    tree.set_root(root);
+    tree
+}
+
+/// Copy the nodes below `root` parents first, each written through
+/// the pointer its parent left for it.
+unsafe fn node_into_c<H: Handle, P: Pool<Elem = Node<H>, Handle = H>>(
+    tree: &Tree<H, P>,
+    root: Option<H>,
+) -> *mut TreeNode {
+    let mut result = null_mut();
+    let mut stack: Vec<(H, *mut *mut TreeNode)> = root
+        .map(|h| (h, &mut result as *mut _))
+        .into_iter()
+        .collect();
+    while let Some((h, parent)) = stack.pop() {
+        let node = malloc_node();
+        write(
+            node,
+            TreeNode {
+                val: tree.val(h),
+                left: null_mut(),
+                right: null_mut(),
+            },
+        );
+        *parent = node;
+        stack.extend(tree.left(h).map(|l| (l, &mut (*node).left as *mut _)));
+        stack.extend(tree.right(h).map(|r| (r, &mut (*node).right as *mut _)));
+    }
+    result
+}
+
+pub unsafe fn tree_into_c<H: Handle, P: Pool<Elem = Node<H>, Handle = H>>(
+    tree: &Tree<H, P>,
+) -> *mut TreeNode {
+    node_into_c(tree, tree.root())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tree_from_c, tree_into_c, Tree, TreeNode};
+    use alloc::vec::Vec;
+    use core::ptr::null_mut;
+
+    fn leaf(val: i32) -> TreeNode {
+        TreeNode {
+            val,
+            left: null_mut(),
+            right: null_mut(),
+        }
+    }
+
+    #[test]
+    fn test_tree_round_trip() {
+        let left = &mut leaf(2);
+        let right = &mut leaf(3);
+        let root = &mut TreeNode {
+            val: 1,
+            left,
+            right,
+        };
+
+        let mut tree: Tree = unsafe { tree_from_c(root) };
+        let r = tree.root().unwrap();
+        assert!(tree.val(r) == 1);
+        let l = tree.left(r).unwrap();
+        assert!(tree.val(l) == 2);
+        assert!(tree.left(l).is_none());
+
+        let n = tree.add(4, None, None);
+        tree.set_right(l, Some(n));
+
+        let p = unsafe { tree_into_c(&tree) };
+        unsafe {
+            assert!((*p).val == 1);
+            assert!((*(*p).left).val == 2);
+            assert!((*(*(*p).left).right).val == 4);
+            assert!((*(*p).right).val == 3);
+            assert!((*(*p).right).left.is_null());
+        }
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let tree: Tree = unsafe { tree_from_c(null_mut()) };
+        assert!(tree.root().is_none());
+        assert!(unsafe { tree_into_c(&tree) }.is_null());
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let n = 100_000;
+        let mut nodes: Vec<TreeNode> = (0..n).map(leaf).collect();
+        for i in (1..nodes.len()).rev() {
+            let (parents, children) = nodes.split_at_mut(i);
+            parents[i - 1].left = &mut children[0];
+        }
+
+        let tree: Tree = unsafe { tree_from_c(&nodes[0]) };
+        let mut depth = 0;
+        let mut h = tree.root();
+        while let Some(x) = h {
+            assert!(tree.val(x) == depth);
+            assert!(tree.right(x).is_none());
+            depth += 1;
+            h = tree.left(x);
+        }
+        assert!(depth == n);
+
+        let mut p = unsafe { tree_into_c(&tree) };
+        let mut depth = 0;
+        while !p.is_null() {
+            unsafe {
+                assert!((*p).val == depth);
+                assert!((*p).right.is_null());
+                p = (*p).left;
+            }
+            depth += 1;
+        }
+        assert!(depth == n);
+    }
+}
@@ -9,7 +9,13 @@
 #![feature(ptr_offset_from)]
 #![feature(is_sorted)]
 #![feature(const_saturating_int_methods)]
+#![feature(const_generics)]
+#![allow(incomplete_features)]
 #![cfg_attr(feature = "online-judge", feature(lang_items))]
+#![cfg_attr(
+    feature = "static-heap",
+    feature(const_if_match, const_loop, const_panic)
+)]
 #![doc(test(attr(feature(proc_macro_hygiene))))]
 #![no_std]
 #![deny(stable_features)]
@@ -79,14 +85,24 @@ pub mod list;
 pub mod pool;
 pub mod stack;
 
+pub mod array;
 pub mod block;
 pub mod chunk;
+pub mod ring;
+pub mod smallvec;
 pub mod string;
 
 pub mod dheap;
 pub mod dlist;
 pub mod flist;
 
+pub mod leetcode;
+
+pub mod checker;
+pub mod gen;
+pub mod random;
+pub mod testing;
+
 #[macro_use]
 pub mod prelude;
 
@@ -103,9 +119,26 @@ pub fn panic(_info: &::core::panic::PanicInfo) -> ! {
     unsafe { ::core::intrinsics::abort() }
 }
 
+// `StaticHeap` is not thread safe, so the multithreaded test harness
+// of porus itself keeps the system allocator.
+
+#[cfg(all(not(feature = "local-judge"), any(not(feature = "static-heap"), test)))]
 #[global_allocator]
 static _A: allocator::System = allocator::System;
 
+#[cfg(all(not(feature = "local-judge"), feature = "static-heap", not(test)))]
+#[global_allocator]
+static _A: allocator::StaticHeap = allocator::StaticHeap;
+
+#[cfg(all(feature = "local-judge", any(not(feature = "static-heap"), test)))]
+#[global_allocator]
+static _A: allocator::Metered<allocator::System> = allocator::Metered::new(allocator::System);
+
+#[cfg(all(feature = "local-judge", feature = "static-heap", not(test)))]
+#[global_allocator]
+static _A: allocator::Metered<allocator::StaticHeap> =
+    allocator::Metered::new(allocator::StaticHeap);
+
 #[cfg(feature = "online-judge")]
 #[alloc_error_handler]
 fn oom(_info: ::core::alloc::Layout) -> ! {
@@ -1,15 +1,78 @@
 use core::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Error(i32);
 
+pub const EPERM: Error = Error(1);
+pub const ENOENT: Error = Error(2);
+pub const EINTR: Error = Error(4);
+pub const EIO: Error = Error(5);
+pub const EBADF: Error = Error(9);
+#[cfg(not(target_os = "macos"))]
+pub const EAGAIN: Error = Error(11);
+#[cfg(target_os = "macos")]
+pub const EAGAIN: Error = Error(35);
+pub const ENOMEM: Error = Error(12);
+pub const EACCES: Error = Error(13);
+pub const EFAULT: Error = Error(14);
+pub const EISDIR: Error = Error(21);
+pub const EINVAL: Error = Error(22);
+pub const ENOSPC: Error = Error(28);
+pub const EPIPE: Error = Error(32);
+
+impl Error {
+    pub const fn errno(self) -> i32 {
+        self.0
+    }
+
+    #[cfg(feature = "local-judge")]
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            EPERM => Some("EPERM"),
+            ENOENT => Some("ENOENT"),
+            EINTR => Some("EINTR"),
+            EIO => Some("EIO"),
+            EBADF => Some("EBADF"),
+            EAGAIN => Some("EAGAIN"),
+            ENOMEM => Some("ENOMEM"),
+            EACCES => Some("EACCES"),
+            EFAULT => Some("EFAULT"),
+            EISDIR => Some("EISDIR"),
+            EINVAL => Some("EINVAL"),
+            ENOSPC => Some("ENOSPC"),
+            EPIPE => Some("EPIPE"),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        #[cfg(feature = "local-judge")]
+        {
+            if let Some(name) = self.name() {
+                return write!(f, "{}({})", name, self.0);
+            }
+        }
+
         write!(f, "Error({})", self.0)
     }
 }
 
+pub const O_RDONLY: i32 = 0;
+pub const O_WRONLY: i32 = 1;
+#[cfg(not(target_os = "macos"))]
+pub const O_CREAT: i32 = 0o100;
+#[cfg(target_os = "macos")]
+pub const O_CREAT: i32 = 0x200;
+#[cfg(not(target_os = "macos"))]
+pub const O_TRUNC: i32 = 0o1000;
+#[cfg(target_os = "macos")]
+pub const O_TRUNC: i32 = 0x400;
+
 extern "C" {
+    pub fn open(path: *const u8, flags: i32, ...) -> i32;
+    pub fn close(fd: i32) -> i32;
     pub fn read(fd: i32, buf: *mut u8, count: usize) -> isize;
     pub fn write(fd: i32, buf: *const u8, count: usize) -> isize;
 
@@ -18,6 +81,10 @@ extern "C" {
     pub fn free(ptr: *mut u8);
     pub fn realloc(ptr: *mut u8, size: usize) -> *mut u8;
 
+    pub fn exit(status: i32) -> !;
+    pub fn abort() -> !;
+    pub fn getenv(name: *const u8) -> *const u8;
+
     #[cfg_attr(target_os = "windows", link_name = "_errno")]
     #[cfg_attr(target_os = "macos", link_name = "__error")]
     #[cfg_attr(target_os = "linux", link_name = "__errno_location")]
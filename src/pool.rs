@@ -25,3 +25,6 @@ pub fn add<P: Pool>(pool: &mut P, item: P::Elem) -> P::Handle {
 pub fn remove<P: Pool>(pool: &mut P, handle: P::Handle) -> P::Elem {
     Pool::remove(pool, handle)
 }
+
+mod iterable;
+pub use self::iterable::{handles, iter, iter_mut, Handles, Iter, IterMut, IterablePool};
@@ -0,0 +1,91 @@
+use super::Pool;
+use alloc::vec::{IntoIter, Vec};
+use core::marker::PhantomData;
+
+/// Pool which knows its live elements, e.g. to walk every node of a
+/// graph.
+pub trait IterablePool: Pool + Sized {
+    /// The first live handle after `handle`, or the first live handle
+    /// if `handle` is `None`.
+    fn next_handle(&self, handle: Option<Self::Handle>) -> Option<Self::Handle>;
+
+    fn handles(&self) -> Handles<Self> {
+        Handles {
+            pool: self,
+            next: IterablePool::next_handle(self, None),
+        }
+    }
+
+    fn iter(&self) -> Iter<Self> {
+        Iter {
+            handles: IterablePool::handles(self),
+        }
+    }
+
+    fn iter_mut(&mut self) -> IterMut<Self> {
+        let handles: Vec<Self::Handle> = IterablePool::handles(self).collect();
+        IterMut {
+            pool: self,
+            handles: handles.into_iter(),
+            _pool: PhantomData,
+        }
+    }
+}
+
+pub struct Handles<'a, P: IterablePool> {
+    pool: &'a P,
+    next: Option<P::Handle>,
+}
+
+impl<'a, P: IterablePool> Iterator for Handles<'a, P> {
+    type Item = P::Handle;
+
+    fn next(&mut self) -> Option<P::Handle> {
+        let handle = self.next?;
+        self.next = IterablePool::next_handle(self.pool, Some(handle));
+        Some(handle)
+    }
+}
+
+pub struct Iter<'a, P: IterablePool> {
+    handles: Handles<'a, P>,
+}
+
+impl<'a, P: IterablePool> Iterator for Iter<'a, P> {
+    type Item = &'a P::Elem;
+
+    fn next(&mut self) -> Option<&'a P::Elem> {
+        let handle = self.handles.next()?;
+        Some(Pool::get(self.handles.pool, handle))
+    }
+}
+
+/// The handles are collected beforehand, so that no element is
+/// borrowed while looking for the next one.
+pub struct IterMut<'a, P: IterablePool> {
+    pool: *mut P,
+    handles: IntoIter<P::Handle>,
+    _pool: PhantomData<&'a mut P>,
+}
+
+impl<'a, P: IterablePool> Iterator for IterMut<'a, P> {
+    type Item = &'a mut P::Elem;
+
+    fn next(&mut self) -> Option<&'a mut P::Elem> {
+        let handle = self.handles.next()?;
+        // every handle is yielded once, so the elements do not alias
+        Some(Pool::get_mut(unsafe { &mut *self.pool }, handle))
+    }
+}
+
+pub fn handles<P: IterablePool>(pool: &P) -> Handles<P> {
+    IterablePool::handles(pool)
+}
+
+pub fn iter<P: IterablePool>(pool: &P) -> Iter<P> {
+    IterablePool::iter(pool)
+}
+
+pub fn iter_mut<P: IterablePool>(pool: &mut P) -> IterMut<P> {
+    IterablePool::iter_mut(pool)
+}
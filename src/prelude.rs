@@ -26,7 +26,10 @@ pub use crate::allocator;
 pub use crate::pool;
 
 pub use crate::allocator::Pool;
+pub use crate::array::Array;
 pub use crate::chunk::Chunk;
+pub use crate::ring::RingBuffer;
+pub use crate::smallvec::SmallVec;
 
 pub use crate::collection::{self, Collection};
 pub use crate::deque::{self, Deque};
@@ -40,6 +43,8 @@ pub use crate::dlist::DoublyLinkedList;
 pub use crate::flist::SinglyLinkedList;
 pub use crate::string::{stringf, String, StringBuffer};
 
+pub use crate::leetcode::leetcode;
+
 /// the porus prelude
 #[macro_export]
 macro_rules! prelude {
@@ -59,10 +64,14 @@ macro_rules! prelude {
 
             #[cfg_attr(not(feature = "online-judge"), main)]
             fn main() {
+                #[cfg(feature = "local-judge")]
+                $crate::allocator::stats::initialize();
                 let stdin = &mut Source::new(0, unsafe { &mut STDIN });
                 let stdout = &mut Sink::new(1, unsafe { &mut STDOUT });
                 initialize(stdin, stdout);
                 super::main();
+                #[cfg(feature = "local-judge")]
+                $crate::allocator::stats::report();
             }
 
             #[cfg(feature = "online-judge")]
@@ -73,8 +82,60 @@ macro_rules! prelude {
             }
         }
     };
+    (checker) => {
+        #[allow(unused_imports)]
+        use $crate::prelude::*;
+        #[allow(unused_imports)]
+        use $crate::checker::{self, quitf, Verdict};
+
+        pub mod __porus_main {
+            use $crate::checker::{initialize, quit, Verdict};
+
+            #[cfg_attr(not(feature = "online-judge"), main)]
+            fn main() {
+                initialize();
+                super::main();
+                quit(Verdict::Accepted, |_| {});
+            }
+
+            #[cfg(feature = "online-judge")]
+            #[export_name = "main"]
+            pub extern "C" fn porus_start(argc: i32, argv: *const *const u8) -> i32 {
+                unsafe { $crate::checker::set_args(argc, argv) };
+                main();
+                0
+            }
+        }
+    };
     (leetcode) => {
         #[allow(unused_imports)]
         use $crate::prelude::*;
     };
+    (leetcode-local) => {
+        prelude!(leetcode - local, 1024);
+    };
+    (leetcode-local, $size:expr) => {
+        #[allow(unused_imports)]
+        use $crate::prelude::*;
+
+        pub mod __porus_main {
+            use $crate::file::{Sink, Source};
+            use $crate::stdio::initialize;
+
+            static mut STDIN: [u8; $size] = [0; $size];
+            static mut STDOUT: [u8; $size] = [0; $size];
+
+            #[main]
+            fn main() {
+                #[cfg(feature = "local-judge")]
+                $crate::allocator::stats::initialize();
+                let stdin = &mut Source::new(0, unsafe { &mut STDIN });
+                let stdout = &mut Sink::new(1, unsafe { &mut STDOUT });
+                initialize(stdin, stdout);
+                super::__porus_leetcode_main();
+                #[cfg(feature = "local-judge")]
+                $crate::allocator::stats::report();
+            }
+        }
+    };
 }
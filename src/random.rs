@@ -0,0 +1,352 @@
+//! Deterministic pseudo random number generators, for randomised
+//! algorithms and generating test data.
+//!
+//! ```
+//! # use porus::prelude::*;
+//! use porus::random::{self, Xoshiro256StarStar};
+//!
+//! let rng = &mut Xoshiro256StarStar::new(42);
+//! let x: usize = random::range(rng, 1, 7);
+//! assert!((1..7).contains(&x));
+//!
+//! let v = &mut vec![1, 2, 3, 4, 5];
+//! random::shuffle(rng, v);
+//! ```
+//!
+//! Generators created with the same seed always produce the same
+//! sequence. To make the seed unpredictable, e.g. for anti-hack
+//! hashing, seed with [`entropy`].
+
+use crate::collection;
+use crate::list::{get, swap, ListMut};
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+}
+
+impl<'a, R: Rng + ?Sized> Rng for &'a mut R {
+    fn next_u64(&mut self) -> u64 {
+        Rng::next_u64(*self)
+    }
+}
+
+pub fn next_u64<R: Rng>(rng: &mut R) -> u64 {
+    Rng::next_u64(rng)
+}
+
+/// [SplitMix64](http://prng.di.unimi.it/splitmix64.c), mostly used
+/// to expand a single `u64` seed into the state of other generators.
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+}
+
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = u64::wrapping_add(self.state, 0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = u64::wrapping_mul(z ^ (z >> 30), 0xbf58_476d_1ce4_e5b9);
+        z = u64::wrapping_mul(z ^ (z >> 27), 0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// [xoshiro256**](http://prng.di.unimi.it/xoshiro256starstar.c)
+pub struct Xoshiro256StarStar {
+    s: [u64; 4],
+}
+
+impl Xoshiro256StarStar {
+    pub fn new(seed: u64) -> Self {
+        let mut sm = SplitMix64::new(seed);
+        Self {
+            s: [
+                next_u64(&mut sm),
+                next_u64(&mut sm),
+                next_u64(&mut sm),
+                next_u64(&mut sm),
+            ],
+        }
+    }
+
+    pub fn from_entropy() -> Self {
+        Self::new(entropy())
+    }
+}
+
+impl Rng for Xoshiro256StarStar {
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = u64::wrapping_mul(u64::rotate_left(u64::wrapping_mul(s[1], 5), 7), 9);
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = u64::rotate_left(s[3], 45);
+
+        result
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn timestamp() -> u64 {
+    unsafe { core::arch::x86_64::_rdtsc() }
+}
+
+#[cfg(target_arch = "x86")]
+fn timestamp() -> u64 {
+    unsafe { core::arch::x86::_rdtsc() }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "x86")))]
+const fn timestamp() -> u64 {
+    0
+}
+
+/// A seed which differs from run to run, mixed from the time stamp
+/// counter and addresses randomised by ASLR.
+#[allow(clippy::cast_possible_truncation)]
+pub fn entropy() -> u64 {
+    let local = 0_u8;
+    let stack = &local as *const u8 as usize as u64;
+    let code = entropy as *const () as usize as u64;
+    let mut sm = SplitMix64::new(timestamp() ^ u64::rotate_left(stack, 32) ^ code);
+    next_u64(&mut sm)
+}
+
+/// A uniformly distributed number in `0..n`, without modulo bias.
+///
+/// See [Fast Random Integer Generation in an
+/// Interval](https://arxiv.org/abs/1805.10941).
+#[allow(clippy::cast_possible_truncation)]
+pub fn below<R: Rng>(rng: &mut R, n: u64) -> u64 {
+    assert!(n > 0, "empty range");
+    let mut m = u128::wrapping_mul(u128::from(next_u64(rng)), u128::from(n));
+    if (m as u64) < n {
+        let threshold = u64::wrapping_rem(u64::wrapping_neg(n), n);
+        while (m as u64) < threshold {
+            m = u128::wrapping_mul(u128::from(next_u64(rng)), u128::from(n));
+        }
+    }
+    (m >> 64) as u64
+}
+
+pub trait Uniform: Sized {
+    /// A uniformly distributed value in `low..high`.
+    fn uniform<R: Rng>(rng: &mut R, low: Self, high: Self) -> Self;
+}
+
+#[doc(hidden)]
+macro uniform($t:ty, $u:ty) {
+    impl Uniform for $t {
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_possible_wrap,
+            clippy::cast_sign_loss,
+            clippy::cast_lossless
+        )]
+        fn uniform<R: Rng>(rng: &mut R, low: Self, high: Self) -> Self {
+            assert!(low < high, "empty range");
+            // the span of a signed range may not fit in the signed type
+            let n = <$u>::wrapping_sub(high as $u, low as $u) as u64;
+            <$u>::wrapping_add(low as $u, below(rng, n) as $u) as $t
+        }
+    }
+}
+
+uniform!(u8, u8);
+uniform!(u16, u16);
+uniform!(u32, u32);
+uniform!(u64, u64);
+uniform!(usize, usize);
+uniform!(i8, u8);
+uniform!(i16, u16);
+uniform!(i32, u32);
+uniform!(i64, u64);
+uniform!(isize, usize);
+
+/// A uniformly distributed value in `low..high`.
+pub fn range<T: Uniform, R: Rng>(rng: &mut R, low: T, high: T) -> T {
+    Uniform::uniform(rng, low, high)
+}
+
+/// A uniformly distributed `f64` in `[0, 1)`.
+#[allow(clippy::cast_precision_loss, clippy::float_arithmetic)]
+pub fn float<R: Rng>(rng: &mut R) -> f64 {
+    (next_u64(rng) >> 11) as f64 * (1.0 / (1_u64 << 53) as f64)
+}
+
+pub fn bool<R: Rng>(rng: &mut R) -> bool {
+    (next_u64(rng) >> 63) == 1
+}
+
+fn index<R: Rng>(rng: &mut R, n: usize) -> usize {
+    range(rng, 0, n)
+}
+
+/// Fisher-Yates shuffle.
+pub fn shuffle<R: Rng, L: ListMut>(rng: &mut R, list: &mut L) {
+    let size = collection::size(list);
+    for i in (1..size).rev() {
+        let j = index(rng, usize::wrapping_add(i, 1));
+        swap(list, i, j);
+    }
+}
+
+/// Move a uniformly chosen sample of `k` elements to the front of
+/// `list`, in random order.
+pub fn sample<R: Rng, L: ListMut>(rng: &mut R, list: &mut L, k: usize) {
+    let size = collection::size(list);
+    assert!(k <= size, "sample larger than list");
+    for i in 0..k {
+        let j = range(rng, i, size);
+        swap(list, i, j);
+    }
+}
+
+/// A uniformly chosen permutation of `0..n`.
+pub fn permutation<R: Rng>(rng: &mut R, n: usize) -> Vec<usize> {
+    let mut p: Vec<usize> = (0..n).collect();
+    shuffle(rng, &mut p);
+    p
+}
+
+/// Edges of a random tree on vertices `0..n`, each vertex attached to
+/// a random earlier one and then relabelled. Not uniform over all
+/// labelled trees, see [`gen::tree`](crate::gen::tree) for that.
+pub fn tree<R: Rng>(rng: &mut R, n: usize) -> Vec<(usize, usize)> {
+    let label = permutation(rng, n);
+    let mut edges: Vec<(usize, usize)> = (1..n)
+        .map(|i| (*get(&label, index(rng, i)), *get(&label, i)))
+        .collect();
+    shuffle(rng, &mut edges);
+    edges
+}
+
+/// Edges of a random simple undirected graph on vertices `0..n` with
+/// `m` edges, i.e. without loops or multiple edges.
+pub fn graph<R: Rng>(rng: &mut R, n: usize, m: usize) -> Vec<(usize, usize)> {
+    let max = usize::checked_mul(n, usize::saturating_sub(n, 1)).map(|x| usize::wrapping_div(x, 2));
+    assert!(max.map_or(true, |max| m <= max), "too many edges");
+
+    let mut seen = BTreeSet::new();
+    let mut edges = Vec::with_capacity(m);
+    while edges.len() < m {
+        let u = index(rng, n);
+        let v = index(rng, n);
+        if u != v && seen.insert((usize::min(u, v), usize::max(u, v))) {
+            edges.push((u, v));
+        }
+    }
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        graph, next_u64, permutation, range, sample, tree, SplitMix64, Xoshiro256StarStar,
+    };
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_splitmix64() {
+        let rng = &mut SplitMix64::new(0);
+        assert!(next_u64(rng) == 0xe220_a839_7b1d_cdaf);
+        assert!(next_u64(rng) == 0x6e78_9e6a_a1b9_65f4);
+    }
+
+    #[test]
+    fn test_xoshiro256starstar() {
+        let rng = &mut Xoshiro256StarStar::new(0);
+        assert!(next_u64(rng) == 0x99ec_5f36_cb75_f2b4);
+        assert!(next_u64(rng) == 0xbf6e_1f78_4956_452a);
+        assert!(next_u64(rng) == 0x1a5f_849d_4933_e6e0);
+    }
+
+    #[test]
+    fn test_range() {
+        let rng = &mut Xoshiro256StarStar::new(1);
+        let v: Vec<i32> = (0..6000).map(|_| range(rng, -3, 3)).collect();
+        for x in -3..3 {
+            let count = v.iter().filter(|&&y| y == x).count();
+            assert!(count > 800 && count < 1200);
+        }
+        assert!(v.iter().all(|&x| -3 <= x && x < 3));
+    }
+
+    #[test]
+    fn test_signed_range() {
+        let rng = &mut Xoshiro256StarStar::new(5);
+        let v: Vec<i8> = (0..1000).map(|_| range(rng, -100, 100)).collect();
+        assert!(v.iter().all(|&x| -100 <= x && x < 100));
+        assert!(v.iter().any(|&x| x < -50) && v.iter().any(|&x| x >= 50));
+
+        for _ in 0..1000 {
+            let x = range(rng, i64::min_value(), i64::max_value());
+            assert!(x < i64::max_value());
+            let x = range(rng, -1_isize, isize::max_value());
+            assert!(-1 <= x && x < isize::max_value());
+        }
+    }
+
+    #[test]
+    fn test_permutation() {
+        let rng = &mut Xoshiro256StarStar::new(2);
+        let mut p = permutation(rng, 100);
+        p.sort();
+        assert!(p == (0..100).collect::<Vec<_>>());
+
+        let v = &mut (0..10).collect::<Vec<usize>>();
+        sample(rng, v, 3);
+        v.sort();
+        assert!(*v == (0..10).collect::<Vec<_>>());
+    }
+
+    fn find(p: &mut Vec<usize>, x: usize) -> usize {
+        if p[x] == x {
+            x
+        } else {
+            let parent = p[x];
+            let r = find(p, parent);
+            p[x] = r;
+            r
+        }
+    }
+
+    #[test]
+    fn test_tree() {
+        let rng = &mut Xoshiro256StarStar::new(3);
+        let edges = tree(rng, 50);
+        assert!(edges.len() == 49);
+
+        let mut parent: Vec<usize> = (0..50).collect();
+        for &(u, v) in &edges {
+            let (ru, rv) = (find(&mut parent, u), find(&mut parent, v));
+            assert!(ru != rv);
+            parent[ru] = rv;
+        }
+    }
+
+    #[test]
+    fn test_graph() {
+        let rng = &mut Xoshiro256StarStar::new(4);
+        let mut edges: Vec<(usize, usize)> = graph(rng, 5, 10)
+            .into_iter()
+            .map(|(u, v)| (usize::min(u, v), usize::max(u, v)))
+            .collect();
+        edges.sort();
+        edges.dedup();
+        assert!(edges.len() == 10);
+        assert!(edges.iter().all(|&(u, v)| u < v));
+    }
+}
@@ -0,0 +1,270 @@
+use crate::block::Block;
+use crate::capacity::{DefaultPolicy, Policy};
+use crate::collection::Collection;
+use crate::deque::Deque;
+use crate::list::{List, ListMut};
+use crate::stack::Stack;
+use alloc::alloc::{Alloc, Global};
+
+/// Circular buffer on a [`Block`], which can be used in place of
+/// `VecDeque`. The elements are `data[front..]` followed by
+/// `data[..front]`, when they wrap around the end of the block.
+pub struct RingBuffer<T, P: Policy = DefaultPolicy, A: Alloc = Global> {
+    front: usize,
+    size: usize,
+    data: Block<T, P, A>,
+}
+
+impl<T, P: Policy, A: Alloc> RingBuffer<T, P, A> {
+    pub fn new(allocator: A, capacity: usize) -> Self {
+        Self {
+            front: 0,
+            size: 0,
+            data: Block::new(allocator, capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    /// Index in the block of the element at `index`.
+    fn physical(&self, index: usize) -> usize {
+        let i = usize::wrapping_add(self.front, index);
+        let capacity = self.data.capacity();
+        if i >= capacity {
+            usize::wrapping_sub(i, capacity)
+        } else {
+            i
+        }
+    }
+
+    fn grow(&mut self) {
+        if self.size < self.data.capacity() {
+            return;
+        }
+
+        if self.front == 0 {
+            assert!(
+                self.data.grow(0) > 0,
+                "ring buffer is full, its capacity policy does not grow"
+            );
+        } else {
+            let n = usize::wrapping_sub(self.data.capacity(), self.front);
+            let grow = self.data.grow(n);
+            assert!(
+                grow > 0,
+                "ring buffer is full, its capacity policy does not grow"
+            );
+            self.front = usize::wrapping_add(self.front, grow);
+        }
+    }
+
+    fn shrink(&mut self) {
+        let capacity = self.data.capacity();
+        let new_capacity = P::shrink(self.size, capacity);
+        if new_capacity >= capacity {
+            return;
+        }
+
+        let end = usize::wrapping_add(self.front, self.size);
+        if end > capacity {
+            // move data[front..] to the end of the shrunk block
+            let n = usize::wrapping_sub(capacity, self.front);
+            let shrink = self.data.shrink(self.size, None, n);
+            self.front = usize::wrapping_sub(self.front, shrink);
+        } else if end > new_capacity {
+            // move the elements to the start of the block
+            self.data.shrink(self.size, Some(self.front), self.size);
+            self.front = 0;
+        } else {
+            self.data.shrink(self.size, None, 0);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        while Deque::pop_back(self).is_some() {}
+    }
+}
+
+impl<T, P: Policy, A: Alloc + Default> RingBuffer<T, P, A> {
+    pub fn new_with_capacity(capacity: usize) -> Self {
+        Self::new(Default::default(), capacity)
+    }
+}
+
+impl<T, P: Policy, A: Alloc + Default> Default for RingBuffer<T, P, A> {
+    fn default() -> Self {
+        Self::new_with_capacity(0)
+    }
+}
+
+impl<T, P: Policy, A: Alloc> Drop for RingBuffer<T, P, A> {
+    fn drop(&mut self) {
+        for index in 0..self.size {
+            let i = self.physical(index);
+            drop(self.data.read(i));
+        }
+    }
+}
+
+impl<T, P: Policy, A: Alloc> Collection for RingBuffer<T, P, A> {
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<T, P: Policy, A: Alloc> List for RingBuffer<T, P, A> {
+    type Elem = T;
+
+    fn get(&self, index: usize) -> Option<&T> {
+        if index < self.size {
+            Some(self.data.get(self.physical(index)))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, P: Policy, A: Alloc> ListMut for RingBuffer<T, P, A> {
+    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.size {
+            let i = self.physical(index);
+            Some(self.data.get_mut(i))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, P: Policy, A: Alloc> Deque for RingBuffer<T, P, A> {
+    type Elem = T;
+
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    fn push_front(&mut self, elem: T) {
+        self.grow();
+        self.front = if self.front == 0 {
+            usize::wrapping_sub(self.data.capacity(), 1)
+        } else {
+            usize::wrapping_sub(self.front, 1)
+        };
+        self.data.write(self.front, elem);
+        self.size = usize::wrapping_add(self.size, 1);
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        if self.size == 0 {
+            return None;
+        }
+
+        let elem = self.data.read(self.front);
+        self.front = self.physical(1);
+        self.size = usize::wrapping_sub(self.size, 1);
+        if self.size == 0 {
+            self.front = 0;
+        }
+        self.shrink();
+        Some(elem)
+    }
+
+    fn push_back(&mut self, elem: T) {
+        self.grow();
+        let i = self.physical(self.size);
+        self.data.write(i, elem);
+        self.size = usize::wrapping_add(self.size, 1);
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        let index = usize::checked_sub(self.size, 1)?;
+        let i = self.physical(index);
+        let elem = self.data.read(i);
+        self.size = index;
+        if self.size == 0 {
+            self.front = 0;
+        }
+        self.shrink();
+        Some(elem)
+    }
+
+    fn front(&self) -> Option<&T> {
+        List::get(self, 0)
+    }
+
+    fn back(&self) -> Option<&T> {
+        List::get(self, usize::checked_sub(self.size, 1)?)
+    }
+}
+
+impl<T, P: Policy, A: Alloc> Stack for RingBuffer<T, P, A> {
+    type Elem = T;
+
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    fn push(&mut self, elem: T) {
+        Deque::push_back(self, elem)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        Deque::pop_back(self)
+    }
+
+    fn top(&self) -> Option<&T> {
+        Deque::back(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RingBuffer;
+    use crate::capacity::FixedPolicy;
+    use crate::collection;
+    use crate::deque::{self, Deque};
+    use crate::list;
+    use crate::random::{self, Xoshiro256StarStar};
+    use alloc::alloc::Global;
+    use alloc::collections::VecDeque;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_wrap_around() {
+        let mut r: RingBuffer<usize, FixedPolicy, Global> = RingBuffer::new_with_capacity(4);
+        for i in 0..3 {
+            deque::push_back(&mut r, i);
+        }
+        assert!(deque::pop_front(&mut r) == 0);
+        deque::push_back(&mut r, 3);
+        deque::push_back(&mut r, 4);
+        assert!(list::iter(&r).collect::<Vec<_>>() == [1, 2, 3, 4]);
+        assert!(*list::get(&r, 3) == 4);
+    }
+
+    #[test]
+    fn test_same_as_vecdeque() {
+        let rng = &mut Xoshiro256StarStar::new(0);
+        let mut r: RingBuffer<usize> = RingBuffer::new_with_capacity(0);
+        let mut q = VecDeque::new();
+        for i in 0..10000 {
+            // grow to a few thousand elements, then drain
+            let push = random::range(rng, 0, 4) < if i < 5000 { 3 } else { 1 };
+            match (push, random::bool(rng)) {
+                (true, true) => {
+                    deque::push_front(&mut r, i);
+                    q.push_front(i);
+                }
+                (true, false) => {
+                    deque::push_back(&mut r, i);
+                    q.push_back(i);
+                }
+                (false, true) => assert!(Deque::pop_front(&mut r) == q.pop_front()),
+                (false, false) => assert!(Deque::pop_back(&mut r) == q.pop_back()),
+            }
+            assert!(collection::size(&r) == q.len());
+            assert!(list::iter(&r).eq(q.iter().cloned()));
+        }
+    }
+}
@@ -188,7 +188,7 @@ impl<'a> Consumer for &'a mut f64 {
         };
 
         let mut int: u64 = 0;
-        fread(s, &mut int);
+        let mut digits = fread(s, &mut int);
 
         let mut exp: i32 = 0;
 
@@ -196,6 +196,7 @@ impl<'a> Consumer for &'a mut f64 {
             s.consume();
 
             while let Some(d) = read_digit(s, 10) {
+                digits = true;
                 int = unwrap(u64::checked_add(
                     unwrap(u64::checked_mul(int, 10)),
                     u64::from(d),
@@ -216,7 +217,7 @@ impl<'a> Consumer for &'a mut f64 {
             *self = sign * powi(10.0, exp) * (int as f64);
         }
 
-        true
+        digits
     }
 }
 
@@ -297,4 +298,27 @@ mod tests {
         let mut x = 0_isize;
         assert!(!fread(source, &mut x));
     }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_float_match() {
+        let source = &mut From::from(b"-1.5e1" as &_);
+        let mut x = 0_f64;
+        assert!(fread(source, &mut x));
+        assert!(x == -15.0);
+
+        let source = &mut From::from(b".5" as &_);
+        let mut x = 0_f64;
+        assert!(fread(source, &mut x));
+        assert!(x == 0.5);
+    }
+
+    #[test]
+    fn test_float_mismatch() {
+        for &s in &[b"" as &[u8], b"-", b".", b"-.", b"x"] {
+            let source = &mut From::from(s);
+            let mut x = 0_f64;
+            assert!(!fread(source, &mut x));
+        }
+    }
 }
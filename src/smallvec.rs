@@ -0,0 +1,272 @@
+use crate::block::Block;
+use crate::capacity::{DefaultPolicy, Policy};
+use crate::collection::Collection;
+use crate::list::{List, ListMut};
+use crate::stack::Stack;
+use alloc::alloc::{Alloc, Global};
+use core::iter::{Extend, FromIterator};
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ptr::{read, write};
+
+/// The allocator is kept next to the inline elements until they are
+/// moved to a `Block`, which owns it from then on.
+struct Inline<T, A, const N: usize> {
+    allocator: A,
+    data: MaybeUninit<[T; N]>,
+}
+
+union Storage<T, P: Policy, A: Alloc, const N: usize> {
+    inline: ManuallyDrop<Inline<T, A, N>>,
+    heap: ManuallyDrop<Block<T, P, A>>,
+}
+
+/// Vector storing up to `N` elements inline, the same way as
+/// `StringBuffer` does for bytes, and moving them to a `Block` once
+/// there are more, e.g. for adjacency lists which are mostly tiny.
+/// The `Block` grows as told by `P`.
+pub struct SmallVec<T, const N: usize, P: Policy = DefaultPolicy, A: Alloc = Global> {
+    size: usize,
+    spilled: bool,
+    storage: Storage<T, P, A, N>,
+}
+
+impl<T, const N: usize, P: Policy, A: Alloc> SmallVec<T, N, P, A> {
+    pub fn new_with_allocator(allocator: A) -> Self {
+        Self {
+            size: 0,
+            spilled: false,
+            storage: Storage {
+                inline: ManuallyDrop::new(Inline {
+                    allocator,
+                    data: MaybeUninit::uninit(),
+                }),
+            },
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        if self.spilled {
+            unsafe { self.storage.heap.capacity() }
+        } else {
+            N
+        }
+    }
+
+    /// Whether the elements have been moved to the heap.
+    pub fn spilled(&self) -> bool {
+        self.spilled
+    }
+
+    fn inline_ptr(&self) -> *const T {
+        unsafe { self.storage.inline.data.as_ptr().cast() }
+    }
+
+    fn inline_mut_ptr(&mut self) -> *mut T {
+        unsafe { self.storage.inline.data.as_mut_ptr().cast() }
+    }
+
+    fn spill(&mut self) {
+        let capacity = P::grow(N);
+        assert!(
+            P::initial(capacity) > N,
+            "small vector is full, its capacity policy does not grow"
+        );
+
+        let allocator = unsafe { read(&self.storage.inline.allocator) };
+        let mut heap = Block::new(allocator, capacity);
+        let inline = self.inline_ptr();
+        for index in 0..self.size {
+            heap.write(index, unsafe { read(inline.add(index)) });
+        }
+        self.storage.heap = ManuallyDrop::new(heap);
+        self.spilled = true;
+    }
+
+    pub fn clear(&mut self) {
+        while Stack::pop(self).is_some() {}
+    }
+}
+
+impl<T, const N: usize, P: Policy, A: Alloc + Default> SmallVec<T, N, P, A> {
+    pub fn new() -> Self {
+        Self::new_with_allocator(Default::default())
+    }
+}
+
+impl<T, const N: usize, P: Policy, A: Alloc + Default> Default for SmallVec<T, N, P, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize, P: Policy, A: Alloc> Drop for SmallVec<T, N, P, A> {
+    fn drop(&mut self) {
+        self.clear();
+        unsafe {
+            if self.spilled {
+                ManuallyDrop::drop(&mut self.storage.heap);
+            } else {
+                ManuallyDrop::drop(&mut self.storage.inline);
+            }
+        }
+    }
+}
+
+impl<T, const N: usize, P: Policy, A: Alloc> Collection for SmallVec<T, N, P, A> {
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<T, const N: usize, P: Policy, A: Alloc> List for SmallVec<T, N, P, A> {
+    type Elem = T;
+
+    fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.size {
+            return None;
+        }
+
+        if self.spilled {
+            Some(unsafe { self.storage.heap.get(index) })
+        } else {
+            Some(unsafe { &*self.inline_ptr().add(index) })
+        }
+    }
+}
+
+impl<T, const N: usize, P: Policy, A: Alloc> ListMut for SmallVec<T, N, P, A> {
+    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.size {
+            return None;
+        }
+
+        if self.spilled {
+            Some(unsafe { self.storage.heap.get_mut(index) })
+        } else {
+            Some(unsafe { &mut *self.inline_mut_ptr().add(index) })
+        }
+    }
+}
+
+impl<T, const N: usize, P: Policy, A: Alloc> Stack for SmallVec<T, N, P, A> {
+    type Elem = T;
+
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    fn push(&mut self, elem: T) {
+        if !self.spilled && self.size == N {
+            self.spill();
+        }
+
+        if self.spilled {
+            let heap = unsafe { &mut *self.storage.heap };
+            if self.size == heap.capacity() {
+                assert!(
+                    heap.grow(0) > 0,
+                    "small vector is full, its capacity policy does not grow"
+                );
+            }
+            heap.write(self.size, elem);
+        } else {
+            let size = self.size;
+            unsafe { write(self.inline_mut_ptr().add(size), elem) };
+        }
+        self.size = usize::wrapping_add(self.size, 1);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let index = usize::checked_sub(self.size, 1)?;
+        self.size = index;
+        if self.spilled {
+            Some(unsafe { self.storage.heap.read(index) })
+        } else {
+            Some(unsafe { read(self.inline_ptr().add(index)) })
+        }
+    }
+
+    fn top(&self) -> Option<&T> {
+        List::get(self, usize::checked_sub(self.size, 1)?)
+    }
+}
+
+impl<T, const N: usize, P: Policy, A: Alloc> Extend<T> for SmallVec<T, N, P, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            Stack::push(self, elem);
+        }
+    }
+}
+
+impl<T, const N: usize, P: Policy, A: Alloc + Default> FromIterator<T> for SmallVec<T, N, P, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = Self::new();
+        v.extend(iter);
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SmallVec;
+    use crate::capacity::{DoublingPolicy, FixedPolicy};
+    use crate::collection;
+    use crate::list;
+    use crate::stack;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_spill() {
+        let mut v: SmallVec<usize, 4> = SmallVec::new();
+        for i in 0..4 {
+            stack::push(&mut v, i);
+        }
+        assert!(!v.spilled());
+
+        stack::push(&mut v, 4);
+        assert!(v.spilled());
+        for i in 5..100 {
+            stack::push(&mut v, i);
+        }
+        assert!(list::iter(&v).collect::<Vec<_>>() == (0..100).collect::<Vec<_>>());
+
+        *list::get_mut(&mut v, 0) = 42;
+        assert!(stack::pop(&mut v) == 99);
+        assert!(collection::size(&v) == 99);
+        assert!(*list::get(&v, 0) == 42);
+    }
+
+    #[test]
+    fn test_empty_inline() {
+        let mut v: SmallVec<usize, 0> = SmallVec::new();
+        stack::push(&mut v, 1);
+        assert!(v.spilled());
+        assert!(*stack::top(&v) == 1);
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let mut v: SmallVec<usize, 4> = (0..3).collect();
+        assert!(!v.spilled());
+        v.extend(3..10);
+        assert!(v.spilled());
+        assert!(list::iter(&v).collect::<Vec<_>>() == (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_policy() {
+        let mut v: SmallVec<usize, 2, DoublingPolicy> = SmallVec::new();
+        v.extend(0..3);
+        assert!(v.capacity() == 4);
+        v.extend(3..5);
+        assert!(v.capacity() == 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "small vector is full")]
+    fn test_fixed_full() {
+        let mut v: SmallVec<usize, 2, FixedPolicy> = SmallVec::new();
+        v.extend(0..3);
+    }
+}
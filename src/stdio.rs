@@ -30,16 +30,49 @@ static mut STDOUT: Output = Output(Cell::new(None));
 #[allow(clippy::option_option)]
 struct PeekableSourceInput {
     source: Input,
-    _peeked: Option<Option<u8>>,
+    peeked: Option<Option<u8>>,
 }
 
+/// The standard input and output, as returned by [`save`] and
+/// restored by [`restore`].
+#[allow(clippy::option_option)]
+pub struct State {
+    stdin: Option<NonNull<dyn Source<Item = u8>>>,
+    peeked: Option<Option<u8>>,
+    stdout: Option<NonNull<dyn Sink>>,
+}
+
+unsafe fn input() -> &'static mut PeekableSourceInput {
+    &mut *(&mut STDIN as *mut _ as *mut PeekableSourceInput)
+}
+
+/// Redirect the standard input and output, discarding any byte
+/// peeked from the previous input.
 pub fn initialize(stdin: *mut dyn Source<Item = u8>, stdout: *mut dyn Sink) {
+    restore(State {
+        stdin: NonNull::new(stdin),
+        peeked: None,
+        stdout: NonNull::new(stdout),
+    });
+}
+
+pub fn save() -> State {
     unsafe {
-        (*(&STDIN as *const _ as *const PeekableSourceInput))
-            .source
-            .0
-            .set(NonNull::new(stdin));
-        STDOUT.0.set(NonNull::new(stdout));
+        let input = input();
+        State {
+            stdin: input.source.0.get(),
+            peeked: input.peeked,
+            stdout: STDOUT.0.get(),
+        }
+    }
+}
+
+pub fn restore(state: State) {
+    unsafe {
+        let input = input();
+        input.source.0.set(state.stdin);
+        input.peeked = state.peeked;
+        STDOUT.0.set(state.stdout);
     }
 }
 
@@ -47,6 +80,10 @@ pub fn read<C: Consumer>(c: C) -> bool {
     unsafe { fread(&mut STDIN, c) }
 }
 
+pub fn eof() -> bool {
+    unsafe { STDIN.eof() }
+}
+
 pub fn read_skip_ws<C: Consumer>(c: C) -> bool {
     read(Whitespace);
     read(c)
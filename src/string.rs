@@ -1,18 +1,36 @@
+use crate::capacity::DefaultPolicy;
 use crate::fmt::{self, fwrite_str};
 #[allow(unused_imports)]
 use crate::fmt::{f, fwrite};
-use crate::io::Sink;
+use crate::io::{Bytes, PeekableSource, Sink};
+use crate::scan::is_whitespace;
+use crate::utils::unwrap;
 use alloc::alloc::{Alloc, Global};
 use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::hash::{Hash, Hasher};
 use core::mem::size_of;
-use core::ops::Deref;
-use core::ptr::NonNull;
+use core::ops::{Add, Deref};
+use core::ptr::{copy, NonNull};
 use core::slice::from_raw_parts;
 use core::str;
 
 mod buffer;
 pub use self::buffer::Buffer as StringBuffer;
 
+/// Bytes stored in place, all of the `Union` but the tag byte, which
+/// overlaps the low byte of `SharedString::counter` and of
+/// `StaticString::_padding` with either endianness.
+const INLINE_CAPACITY: usize = usize::wrapping_sub(usize::wrapping_mul(3, size_of::<usize>()), 1);
+
+/// Shared bytes follow a header of the reference count and the length
+/// of the allocation, as a substring may cover only part of it.
+const HEADER_SIZE: usize = usize::wrapping_mul(2, size_of::<usize>());
+
+fn inline_length(length: usize) -> u8 {
+    unwrap(u8::try_from((length << 2) | 1))
+}
+
 #[cfg(target_endian = "little")]
 #[derive(Clone, Copy)]
 struct SharedString {
@@ -21,11 +39,11 @@ struct SharedString {
     s: NonNull<u8>,
 }
 
-#[cfg(all(target_endian = "little", target_pointer_width = "64"))]
+#[cfg(target_endian = "little")]
 #[derive(Clone, Copy)]
 struct InlineString {
     length: u8,
-    s: [u8; 23],
+    s: [u8; INLINE_CAPACITY],
 }
 
 #[cfg(target_endian = "little")]
@@ -44,10 +62,10 @@ struct SharedString {
     counter: NonNull<usize>,
 }
 
-#[cfg(all(target_endian = "big", target_pointer_width = "64"))]
+#[cfg(target_endian = "big")]
 #[derive(Clone, Copy)]
 struct InlineString {
-    s: [u8; 23],
+    s: [u8; INLINE_CAPACITY],
     length: u8,
 }
 
@@ -59,12 +77,29 @@ struct StaticString {
     _padding: usize,
 }
 
+impl SharedString {
+    /// Start of the allocated bytes, after the header.
+    unsafe fn data(&self) -> *mut u8 {
+        self.counter.as_ptr().add(2).cast()
+    }
+
+    unsafe fn set_header(&mut self, counter: usize, length: usize) {
+        *self.counter.as_ptr() = counter;
+        *self.counter.as_ptr().add(1) = length;
+    }
+
+    unsafe fn retain(&self) {
+        *self.counter.as_ptr() = usize::wrapping_add(*self.counter.as_ref(), 1);
+    }
+}
+
 union Union {
     shared: SharedString,
     inline: InlineString,
     static_: StaticString,
 }
 
+#[derive(Clone, Copy, PartialEq)]
 enum Tag {
     Shared,
     Inline,
@@ -109,8 +144,7 @@ impl Clone for Union {
         unsafe {
             match self.tag() {
                 Tag::Shared => {
-                    *self.shared.counter.as_ptr() =
-                        usize::wrapping_add(*self.shared.counter.as_ref(), 1);
+                    self.shared.retain();
                     Self {
                         shared: Clone::clone(&self.shared),
                     }
@@ -146,6 +180,158 @@ impl<A: Alloc + Default> From<&'static [u8]> for String<A> {
     }
 }
 
+/// Copies `s`, unlike `From<&'static [u8]>`.
+impl<'a, A: Alloc + Default> From<&'a str> for String<A> {
+    fn from(s: &'a str) -> Self {
+        let mut buffer: StringBuffer<DefaultPolicy, A> = StringBuffer::new_with_capacity(s.len());
+        buffer.push_str(s);
+        From::from(buffer)
+    }
+}
+
+/// Byte-level counterparts of the methods of `str`, which may be
+/// reached through `Deref` otherwise.
+impl<A: Alloc> String<A> {
+    pub fn find<T: AsRef<[u8]>>(&self, pat: T) -> Option<usize> {
+        let pat = AsRef::<[u8]>::as_ref(&pat);
+        if pat.is_empty() {
+            return Some(0);
+        }
+        self.as_bytes().windows(pat.len()).position(|w| w == pat)
+    }
+
+    pub fn rfind<T: AsRef<[u8]>>(&self, pat: T) -> Option<usize> {
+        let pat = AsRef::<[u8]>::as_ref(&pat);
+        if pat.is_empty() {
+            return Some(self.len());
+        }
+        self.as_bytes().windows(pat.len()).rposition(|w| w == pat)
+    }
+
+    pub fn starts_with<T: AsRef<[u8]>>(&self, pat: T) -> bool {
+        self.as_bytes().starts_with(AsRef::<[u8]>::as_ref(&pat))
+    }
+
+    pub fn ends_with<T: AsRef<[u8]>>(&self, pat: T) -> bool {
+        self.as_bytes().ends_with(AsRef::<[u8]>::as_ref(&pat))
+    }
+}
+
+impl<A: Alloc + Clone> String<A> {
+    /// Bytes `start..end`, sharing the allocation of a shared string
+    /// instead of copying.
+    pub fn substring(&self, start: usize, end: usize) -> Self {
+        assert!(start <= end && end <= self.len(), "index out of bounds");
+        let length = usize::wrapping_sub(end, start);
+        let s = unsafe {
+            match self.s.tag() {
+                Tag::Shared => {
+                    self.s.shared.retain();
+                    Union {
+                        shared: SharedString {
+                            counter: self.s.shared.counter,
+                            length,
+                            s: unwrap(NonNull::new(self.s.shared.s.as_ptr().add(start))),
+                        },
+                    }
+                }
+                Tag::Inline => {
+                    let mut inline = self.s.inline;
+                    copy(inline.s.as_ptr().add(start), inline.s.as_mut_ptr(), length);
+                    inline.length = inline_length(length);
+                    Union { inline }
+                }
+                Tag::Static => Union {
+                    static_: StaticString {
+                        s: self.s.static_.s.add(start),
+                        length,
+                        _padding: 2,
+                    },
+                },
+            }
+        };
+
+        Self {
+            s,
+            allocator: Clone::clone(&self.allocator),
+        }
+    }
+
+    /// Substrings separated by ASCII whitespace, see `substring`.
+    pub fn split_whitespace(&self) -> SplitWhitespace<A> {
+        SplitWhitespace { s: self, offset: 0 }
+    }
+}
+
+impl<A: Alloc + Default> String<A> {
+    /// Copy with every occurrence of `from` replaced by `to`.
+    pub fn replace<T: AsRef<[u8]>, U: AsRef<[u8]>>(&self, from: T, to: U) -> Self {
+        let from = AsRef::<[u8]>::as_ref(&from);
+        let to = AsRef::<[u8]>::as_ref(&to);
+        assert!(!from.is_empty(), "empty pattern");
+
+        let bytes = self.as_bytes();
+        let mut buffer: StringBuffer<DefaultPolicy, A> =
+            StringBuffer::new_with_capacity(bytes.len());
+        let mut offset = 0;
+        while let Some(rest) = bytes.get(offset..) {
+            match rest.windows(from.len()).position(|w| w == from) {
+                None => {
+                    buffer.push_str(rest);
+                    break;
+                }
+                Some(i) => {
+                    buffer.push_str(rest.get(..i).expect("index out of bounds"));
+                    buffer.push_str(to);
+                    offset = usize::wrapping_add(offset, usize::wrapping_add(i, from.len()));
+                }
+            }
+        }
+        From::from(buffer)
+    }
+
+    fn map_bytes<F: Fn(u8) -> u8>(&self, f: F) -> Self {
+        let mut buffer: StringBuffer<DefaultPolicy, A> =
+            StringBuffer::new_with_capacity(self.len());
+        for &c in self.as_bytes() {
+            Sink::write(&mut buffer, f(c));
+        }
+        From::from(buffer)
+    }
+
+    /// Copy with ASCII letters in upper case.
+    pub fn to_uppercase(&self) -> Self {
+        self.map_bytes(|c| c.to_ascii_uppercase())
+    }
+
+    /// Copy with ASCII letters in lower case.
+    pub fn to_lowercase(&self) -> Self {
+        self.map_bytes(|c| c.to_ascii_lowercase())
+    }
+}
+
+pub struct SplitWhitespace<'a, A: Alloc = Global> {
+    s: &'a String<A>,
+    offset: usize,
+}
+
+impl<'a, A: Alloc + Clone> Iterator for SplitWhitespace<'a, A> {
+    type Item = String<A>;
+
+    fn next(&mut self) -> Option<String<A>> {
+        let bytes = self.s.as_bytes();
+        let rest = bytes.get(self.offset..)?;
+        let start = usize::wrapping_add(self.offset, rest.iter().position(|&c| !is_whitespace(c))?);
+        let rest = bytes.get(start..)?;
+        let end = rest
+            .iter()
+            .position(|&c| is_whitespace(c))
+            .map_or(bytes.len(), |i| usize::wrapping_add(start, i));
+        self.offset = end;
+        Some(self.s.substring(start, end))
+    }
+}
+
 impl<A: Alloc> Deref for String<A> {
     type Target = str;
 
@@ -161,15 +347,54 @@ impl<A: Alloc> AsRef<[u8]> for String<A> {
     }
 }
 
+impl<'a, A: Alloc> From<&'a String<A>> for PeekableSource<Bytes<'a>> {
+    fn from(s: &'a String<A>) -> Self {
+        Self::new(Bytes::new(s.as_ref()))
+    }
+}
+
 impl<A: Alloc> PartialEq for String<A> {
     fn eq(&self, other: &Self) -> bool {
         PartialEq::eq(self.as_ref(), other.as_ref())
     }
 }
 
+impl<'a, A: Alloc> PartialEq<&'a str> for String<A> {
+    fn eq(&self, other: &&'a str) -> bool {
+        PartialEq::eq(self.as_ref(), other.as_bytes())
+    }
+}
+
+impl<A: Alloc> Eq for String<A> {}
+
 impl<A: Alloc> PartialOrd for String<A> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        PartialOrd::partial_cmp(self.as_ref(), other.as_ref())
+        Some(Ord::cmp(self, other))
+    }
+}
+
+impl<A: Alloc> Ord for String<A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(self.as_ref(), other.as_ref())
+    }
+}
+
+impl<A: Alloc> Hash for String<A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(self.as_ref(), state)
+    }
+}
+
+impl<'a, A: Alloc + Default, T: AsRef<[u8]> + ?Sized> Add<&'a T> for &'a String<A> {
+    type Output = String<A>;
+
+    fn add(self, other: &'a T) -> String<A> {
+        let other = AsRef::<[u8]>::as_ref(other);
+        let mut buffer: StringBuffer<DefaultPolicy, A> =
+            StringBuffer::new_with_capacity(usize::wrapping_add(self.len(), other.len()));
+        buffer.push_str(self);
+        buffer.push_str(other);
+        From::from(buffer)
     }
 }
 
@@ -186,13 +411,16 @@ impl<A: Alloc> Drop for String<A> {
     fn drop(&mut self) {
         if let Tag::Shared = self.s.tag() {
             unsafe {
-                if let Some(c) = usize::checked_sub(*self.s.shared.counter.as_ref(), 1) {
-                    *self.s.shared.counter.as_mut() = c;
+                let mut shared = self.s.shared;
+                let counter = usize::wrapping_sub(*shared.counter.as_ref(), 1);
+                if counter > 0 {
+                    *shared.counter.as_mut() = counter;
                 } else {
+                    let length = *shared.counter.as_ptr().add(1);
                     Alloc::dealloc_array::<u8>(
                         &mut self.allocator,
-                        self.s.shared.counter.cast(),
-                        usize::wrapping_add(size_of::<usize>(), self.s.shared.length),
+                        shared.counter.cast(),
+                        usize::wrapping_add(HEADER_SIZE, length),
                     )
                     .expect("dealloc failed");
                 }
@@ -228,8 +456,51 @@ pub macro stringf($($arg:tt)*) {
 
 #[cfg(test)]
 mod tests {
-    use super::{String, StringBuffer};
+    use super::{String, StringBuffer, Tag, Union, INLINE_CAPACITY};
+    use crate::capacity::FixedPolicy;
+    use crate::io::Sink;
     use crate::scan::fread;
+    use alloc::vec::Vec;
+    use core::hash::{Hash, Hasher};
+    use core::mem::{align_of, size_of};
+
+    #[test]
+    fn test_layout() {
+        assert!(size_of::<Union>() == size_of::<usize>() * 3);
+        assert!(align_of::<Union>() == align_of::<usize>());
+        assert!(INLINE_CAPACITY == size_of::<Union>() - 1);
+    }
+
+    fn string_of_size(n: usize) -> String {
+        let mut buffer = <StringBuffer as Default>::default();
+        for i in 0..n {
+            Sink::write(&mut buffer, b'a' + (i % 26) as u8);
+        }
+        From::from(buffer)
+    }
+
+    #[test]
+    fn test_inline_capacity() {
+        let s = string_of_size(INLINE_CAPACITY);
+        assert!(s.s.tag() == Tag::Inline);
+        assert!(s.len() == INLINE_CAPACITY);
+
+        let s = string_of_size(INLINE_CAPACITY + 1);
+        assert!(s.s.tag() == Tag::Shared);
+        assert!(s.len() == INLINE_CAPACITY + 1);
+        assert!(s
+            .as_bytes()
+            .iter()
+            .enumerate()
+            .all(|(i, &c)| c == b'a' + (i % 26) as u8));
+    }
+
+    #[test]
+    fn test_static_tag() {
+        let s: String = From::from(b"abcdefghijklmnopqrstuvwxyz" as &'static [u8]);
+        assert!(s.s.tag() == Tag::Static);
+        assert!(s.len() == 26);
+    }
 
     #[test]
     fn test_inline_string_buffer() {
@@ -250,4 +521,135 @@ mod tests {
         let s2: String = From::from(b"abcdefghijklmnopqrstuvwxyz" as &'static [u8]);
         assert!(s1 == s2);
     }
+
+    #[test]
+    fn test_substring() {
+        let s: String = From::from("hello, shared world of string views");
+        assert!(s.s.tag() == Tag::Shared);
+        let t = s.substring(7, 13);
+        assert!(t.s.tag() == Tag::Shared);
+        assert!(t == "shared");
+        assert!(t.substring(1, 3) == "ha");
+        drop(s);
+        assert!(t == "shared");
+
+        let s: String = From::from("short");
+        assert!(s.s.tag() == Tag::Inline);
+        assert!(s.substring(1, 4) == "hor");
+
+        let s: String = From::from(b"static" as &'static [u8]);
+        assert!(s.substring(2, 6) == "atic");
+    }
+
+    #[test]
+    fn test_search() {
+        let s: String = From::from("abcabcab");
+        assert!(s.find("ca") == Some(2));
+        assert!(s.rfind("ca") == Some(5));
+        assert!(s.find("x").is_none());
+        assert!(s.starts_with("abc"));
+        assert!(s.ends_with(b"ab"));
+        assert!(s.replace("ab", "X") == "XcXcX");
+        assert!(s.to_uppercase() == "ABCABCAB");
+        assert!(s.to_uppercase().to_lowercase() == s);
+    }
+
+    #[test]
+    fn test_split_whitespace() {
+        let s: String = From::from("  this is\ta longer sentence\n");
+        let words: Vec<String> = s.split_whitespace().collect();
+        assert!(words.len() == 5);
+        assert!(words[0] == "this");
+        assert!(words[4] == "sentence");
+    }
+
+    #[test]
+    fn test_ord_and_add() {
+        let a: String = From::from("abc");
+        let b: String = From::from("abd");
+        assert!(a < b);
+        assert!(Ord::max(&a, &b) == &b);
+        let c = &(&a + &b) + " and more";
+        assert!(c == "abcabd and more");
+    }
+
+    /// FNV-1a, since `core` has no hasher.
+    struct Fnv(u64);
+
+    impl Hasher for Fnv {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            for &b in bytes {
+                self.0 = (self.0 ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+
+    fn hash<T: Hash + ?Sized>(x: &T) -> u64 {
+        let mut h = Fnv(0xcbf2_9ce4_8422_2325);
+        x.hash(&mut h);
+        h.finish()
+    }
+
+    #[test]
+    fn test_hash() {
+        let bytes = b"hello, shared world of string views";
+        let shared: String = From::from("hello, shared world of string views");
+        let fixed: String = From::from(bytes as &'static [u8]);
+        assert!(hash(&shared) == hash(bytes as &[u8]));
+        assert!(hash(&shared) == hash(&fixed));
+
+        let inline: String = From::from("shared");
+        assert!(inline.s.tag() == Tag::Inline);
+        assert!(hash(&shared.substring(7, 13)) == hash(&inline));
+        assert!(hash(&inline) != hash(&shared));
+    }
+
+    #[test]
+    fn test_buffer() {
+        let mut buffer = <StringBuffer as Default>::default();
+        buffer.push_str("abc");
+        assert!(buffer.pop() == Some(b'c'));
+        buffer.push_str("defghijklmnopqrstuvwxyz");
+        buffer.truncate(4);
+        assert!(buffer.as_ref() == b"abde");
+        buffer.clear();
+        assert!(buffer.pop().is_none());
+
+        let mut buffer: StringBuffer = StringBuffer::new_with_capacity(100);
+        buffer.push_str("xyz");
+        let s: String = From::from(buffer);
+        assert!(s == "xyz");
+    }
+
+    #[test]
+    fn test_fixed_buffer() {
+        let mut buffer: StringBuffer<FixedPolicy> = StringBuffer::new_with_capacity(100);
+        for _ in 0..100 {
+            Sink::write(&mut buffer, b'x');
+        }
+        let s: String = From::from(buffer);
+        assert!(s.len() == 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "string buffer is full")]
+    fn test_fixed_buffer_full() {
+        let mut buffer: StringBuffer<FixedPolicy> = StringBuffer::new_with_capacity(100);
+        for _ in 0..101 {
+            Sink::write(&mut buffer, b'x');
+        }
+    }
+
+    #[test]
+    fn test_string_source() {
+        let s: String = From::from(b"12 34" as &'static [u8]);
+        let source = &mut From::from(&s);
+        let mut x = 0_usize;
+        fread(source, &mut x);
+        assert!(x == 12);
+    }
 }
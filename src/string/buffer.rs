@@ -1,15 +1,20 @@
 use super::Tag::{Inline, Shared, Static};
-use super::{InlineString, SharedString, String, Union};
+use super::{
+    inline_length, InlineString, SharedString, String, Union, HEADER_SIZE, INLINE_CAPACITY,
+};
 use crate::capacity::{DefaultPolicy, Policy};
 use crate::io::{PeekableSource, Sink, Source};
 use crate::scan::{is_whitespace, Consumer};
 use crate::utils::unwrap;
 use alloc::alloc::{Alloc, Global};
 use core::marker::PhantomData;
-use core::mem::{forget, size_of, transmute_copy};
+use core::mem::{forget, transmute_copy};
 use core::ptr::{copy_nonoverlapping, NonNull};
 use core::slice::{from_raw_parts, from_raw_parts_mut};
 
+/// Growable byte string, stored in place while it fits, like a
+/// [`String`]. While buffering, `SharedString::length` is the
+/// capacity and `SharedString::s` points past the last byte.
 pub struct Buffer<P: Policy = DefaultPolicy, A: Alloc = Global> {
     buffer: Union,
     allocator: A,
@@ -21,14 +26,20 @@ impl<P: Policy, A: Alloc + Default> Buffer<P, A> {
         Self {
             buffer: Union {
                 inline: InlineString {
-                    length: 1,
-                    s: [0; size_of::<Union>() - 1],
+                    length: inline_length(0),
+                    s: [0; INLINE_CAPACITY],
                 },
             },
             allocator: Default::default(),
             _policy: PhantomData,
         }
     }
+
+    pub fn new_with_capacity(capacity: usize) -> Self {
+        let mut buffer = Self::new();
+        buffer.reserve(capacity);
+        buffer
+    }
 }
 
 impl<P: Policy, A: Alloc + Default> Default for Buffer<P, A> {
@@ -40,7 +51,7 @@ impl<P: Policy, A: Alloc + Default> Default for Buffer<P, A> {
 unsafe fn as_ptr(u: &Union) -> *const u8 {
     match u.tag() {
         Inline => u.inline.s.as_ptr(),
-        Shared => u.shared.counter.as_ptr().add(1).cast(),
+        Shared => u.shared.data(),
         Static => unreachable!(),
     }
 }
@@ -48,7 +59,7 @@ unsafe fn as_ptr(u: &Union) -> *const u8 {
 unsafe fn as_mut_ptr(u: &mut Union) -> *mut u8 {
     match u.tag() {
         Inline => u.inline.s.as_mut_ptr(),
-        Shared => u.shared.counter.as_ptr().add(1).cast(),
+        Shared => u.shared.data(),
         Static => unreachable!(),
     }
 }
@@ -62,12 +73,11 @@ unsafe fn capacity(u: &Union) -> usize {
 }
 
 unsafe fn resize<A: Alloc>(allocator: &mut A, s: &mut SharedString, new_size: usize) {
-    let counter_size = size_of::<usize>();
     s.counter = Alloc::realloc_array::<u8>(
         allocator,
         s.counter.cast(),
-        usize::wrapping_add(counter_size, s.length),
-        usize::wrapping_add(counter_size, new_size),
+        usize::wrapping_add(HEADER_SIZE, s.length),
+        usize::wrapping_add(HEADER_SIZE, new_size),
     )
     .expect("realloc failed")
     .cast();
@@ -83,73 +93,116 @@ fn len(u: &Union) -> usize {
     }
 }
 
-impl<P: Policy, A: Alloc> AsRef<[u8]> for Buffer<P, A> {
-    fn as_ref(&self) -> &[u8] {
-        let buf = &self.buffer;
-        unsafe { from_raw_parts(as_ptr(buf), len(buf)) }
-    }
-}
-
-impl<P: Policy, A: Alloc> AsMut<[u8]> for Buffer<P, A> {
-    fn as_mut(&mut self) -> &mut [u8] {
-        let buf = &mut self.buffer;
-        unsafe { from_raw_parts_mut(as_mut_ptr(buf), len(buf)) }
+fn set_len(u: &mut Union, length: usize) {
+    match u.tag() {
+        Inline => unsafe { u.inline.length = inline_length(length) },
+        Shared => unsafe { u.shared.s = unwrap(NonNull::new(u.shared.data().add(length))) },
+        Static => unreachable!(),
     }
 }
 
-impl<P: Policy, A: Alloc> Sink for Buffer<P, A> {
-    fn write(&mut self, c: u8) {
-        let offset = len(&self.buffer);
+impl<P: Policy, A: Alloc> Buffer<P, A> {
+    /// Make room for at least `additional` more bytes.
+    pub fn reserve(&mut self, additional: usize) {
+        let length = len(&self.buffer);
+        let required = unwrap(usize::checked_add(length, additional));
         let capacity = unsafe { capacity(&self.buffer) };
+        if required <= capacity {
+            return;
+        }
+
+        let mut new_capacity = match self.buffer.tag() {
+            Inline => P::initial(required),
+            Shared => capacity,
+            Static => unreachable!(),
+        };
+        while new_capacity < required {
+            let grow = P::grow(new_capacity);
+            assert!(
+                grow > new_capacity,
+                "string buffer is full, its capacity policy does not grow"
+            );
+            new_capacity = grow;
+        }
 
-        match self.buffer.tag() {
-            Inline => unsafe {
-                if offset < capacity {
-                    *self.buffer.inline.s.get_unchecked_mut(offset) = c;
-
-                    #[allow(clippy::integer_arithmetic)]
-                    #[allow(clippy::cast_possible_truncation)]
-                    {
-                        self.buffer.inline.length = ((offset as u8 + 1) << 2) | 1;
-                    }
-                } else {
-                    let counter_size = size_of::<usize>();
-                    let new_capacity = P::grow(P::initial(capacity));
+        unsafe {
+            match self.buffer.tag() {
+                Inline => {
                     let s = Alloc::alloc_array::<u8>(
                         &mut self.allocator,
-                        usize::wrapping_add(counter_size, new_capacity),
+                        usize::wrapping_add(HEADER_SIZE, new_capacity),
                     )
                     .expect("alloc failed");
-
                     copy_nonoverlapping(
-                        self.buffer.as_ptr(),
-                        s.as_ptr().add(counter_size),
-                        capacity,
+                        self.buffer.inline.s.as_ptr(),
+                        s.as_ptr().add(HEADER_SIZE),
+                        length,
                     );
-
                     self.buffer.shared.counter = s.cast();
                     self.buffer.shared.length = new_capacity;
-                    self.buffer.shared.s =
-                        unwrap(NonNull::new(as_mut_ptr(&mut self.buffer).add(capacity)));
-                    Sink::write(self, c)
-                }
-            },
-            Shared => unsafe {
-                if self.buffer.as_ptr() == as_ptr(&self.buffer).add(capacity) {
-                    resize(
-                        &mut self.allocator,
-                        &mut self.buffer.shared,
-                        P::grow(capacity),
-                    );
-                    self.buffer.shared.s =
-                        unwrap(NonNull::new(as_mut_ptr(&mut self.buffer).add(offset)));
                 }
+                Shared => resize(&mut self.allocator, &mut self.buffer.shared, new_capacity),
+                Static => unreachable!(),
+            }
+        }
+        set_len(&mut self.buffer, length);
+    }
 
-                *self.buffer.shared.s.as_mut() = c;
-                self.buffer.shared.s = unwrap(NonNull::new(self.buffer.shared.s.as_ptr().add(1)));
-            },
-            Static => unreachable!(),
+    /// Append the bytes of `s`.
+    pub fn push_str<T: AsRef<[u8]>>(&mut self, s: T) {
+        let s = AsRef::<[u8]>::as_ref(&s);
+        self.reserve(s.len());
+        let length = len(&self.buffer);
+        unsafe {
+            copy_nonoverlapping(
+                s.as_ptr(),
+                as_mut_ptr(&mut self.buffer).add(length),
+                s.len(),
+            );
         }
+        set_len(&mut self.buffer, usize::wrapping_add(length, s.len()));
+    }
+
+    /// Remove the last byte.
+    pub fn pop(&mut self) -> Option<u8> {
+        let length = usize::checked_sub(len(&self.buffer), 1)?;
+        let c = unsafe { *as_ptr(&self.buffer).add(length) };
+        set_len(&mut self.buffer, length);
+        Some(c)
+    }
+
+    /// Keep only the first `length` bytes, keeping the capacity.
+    pub fn truncate(&mut self, length: usize) {
+        if length < len(&self.buffer) {
+            set_len(&mut self.buffer, length);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<P: Policy, A: Alloc> AsRef<[u8]> for Buffer<P, A> {
+    fn as_ref(&self) -> &[u8] {
+        let buf = &self.buffer;
+        unsafe { from_raw_parts(as_ptr(buf), len(buf)) }
+    }
+}
+
+impl<P: Policy, A: Alloc> AsMut<[u8]> for Buffer<P, A> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        let buf = &mut self.buffer;
+        unsafe { from_raw_parts_mut(as_mut_ptr(buf), len(buf)) }
+    }
+}
+
+impl<P: Policy, A: Alloc> Sink for Buffer<P, A> {
+    fn write(&mut self, c: u8) {
+        self.reserve(1);
+        let length = len(&self.buffer);
+        unsafe { *as_mut_ptr(&mut self.buffer).add(length) = c };
+        set_len(&mut self.buffer, usize::wrapping_add(length, 1));
     }
 }
 
@@ -175,7 +228,7 @@ impl<P: Policy, A: Alloc> Drop for Buffer<P, A> {
                 Alloc::dealloc_array::<u8>(
                     &mut self.allocator,
                     self.buffer.shared.counter.cast(),
-                    usize::wrapping_add(size_of::<usize>(), capacity(&self.buffer)),
+                    usize::wrapping_add(HEADER_SIZE, capacity(&self.buffer)),
                 )
                 .expect("dealloc failed");
             }
@@ -190,8 +243,8 @@ impl<P: Policy, A: Alloc> From<Buffer<P, A>> for String<A> {
             if let Shared = x.buffer.tag() {
                 let length = len(&x.buffer);
                 resize(&mut x.allocator, &mut x.buffer.shared, length);
-                x.buffer.shared.s = unwrap(NonNull::new(as_mut_ptr(&mut x.buffer)));
-                *x.buffer.shared.counter.as_mut() = 1;
+                x.buffer.shared.s = unwrap(NonNull::new(x.buffer.shared.data()));
+                x.buffer.shared.set_header(1, length);
             }
 
             let s = transmute_copy(&x);
@@ -0,0 +1,76 @@
+//! Run solutions in-process with the standard input and output
+//! redirected, e.g. for [`stress`] testing.
+
+use crate::io::{Bytes, Source};
+use crate::stdio;
+use alloc::vec::Vec;
+use core::mem::transmute;
+
+pub mod stress;
+
+pub use self::stress::stress;
+
+/// Restores the standard input and output when dropped.
+struct Restore(Option<stdio::State>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        if let Some(state) = self.0.take() {
+            stdio::restore(state);
+        }
+    }
+}
+
+/// Run `f` with `input` as the standard input, and return what it
+/// writes to the standard output. The standard input and output are
+/// restored afterwards, also if `f` panics.
+///
+/// ```
+/// # use porus::prelude::*;
+/// let output = porus::testing::capture(b"1 2", || {
+///     let (a, b): (isize, isize) = (read!(), read!());
+///     writelnf!("{:d}", a + b);
+/// });
+/// assert_eq!(b"3\n", output.as_slice());
+/// ```
+pub fn capture<F: FnOnce()>(input: &[u8], f: F) -> Vec<u8> {
+    let mut stdin = Bytes::new(input);
+    let mut stdout = Vec::new();
+
+    {
+        let _restore = Restore(Some(stdio::save()));
+        let source: *mut (dyn Source<Item = u8> + '_) = &mut stdin;
+        // `stdin` outlives its use, the previous input is restored
+        // when `_restore` is dropped, before `stdin`
+        stdio::initialize(unsafe { transmute(source) }, &mut stdout);
+        f();
+    }
+
+    stdout
+}
+
+#[cfg(test)]
+mod tests {
+    use super::capture;
+    use crate::stdio::{read, writelnf};
+    extern crate std;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    #[test]
+    fn test_restore_on_panic() {
+        let output = capture(b"1 2", || {
+            let a: usize = read!();
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                capture(b"3", || {
+                    let _: usize = read!();
+                    panic!("solution failed");
+                })
+            }));
+            assert!(result.is_err());
+            // reads from and writes to the outer capture again
+            let b: usize = read!();
+            writelnf!("{:d}", usize::wrapping_add(a, b));
+        });
+        assert!(output.as_slice() == b"3\n");
+    }
+}
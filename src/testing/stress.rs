@@ -0,0 +1,171 @@
+//! Compare a candidate solution against a reference solution, e.g. a
+//! brute force one, on randomly generated input.
+//!
+//! ```
+//! # use porus::prelude::*;
+//! fn reference() {
+//!     let n: usize = read!();
+//!     writelnf!("{:d}", (1..=n).sum::<usize>());
+//! }
+//!
+//! fn candidate() {
+//!     let n: usize = read!();
+//!     writelnf!("{:d}", n * (n + 1) / 2);
+//! }
+//!
+//! let result = porus::testing::stress(
+//!     |seed, size, sink: &mut Vec<u8>| {
+//!         fmt::fwrite(sink, f!("{:d}\n", seed as usize % size));
+//!     },
+//!     reference,
+//!     candidate,
+//!     1000,
+//!     100,
+//! );
+//! assert!(result.is_ok());
+//! ```
+
+use super::capture;
+use crate::fmt::{f, fwrite, fwrite_str};
+use crate::io::Sink;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Number of seeds tried for each size when searching for a smaller
+/// counterexample.
+pub const SEARCH_SEEDS: u64 = 100;
+
+/// An input on which the outputs of the two solutions differ.
+pub struct Counterexample {
+    pub seed: u64,
+    pub size: usize,
+    pub input: Vec<u8>,
+    pub expected: Vec<u8>,
+    pub actual: Vec<u8>,
+}
+
+impl Counterexample {
+    pub fn write<S: Sink>(&self, s: &mut S) {
+        fwrite(s, f!("seed {:d}, size {:d}\n", self.seed, self.size));
+        fwrite_str(s, "input:\n");
+        fwrite_str(s, &self.input);
+        fwrite_str(s, "expected:\n");
+        fwrite_str(s, &self.expected);
+        fwrite_str(s, "found:\n");
+        fwrite_str(s, &self.actual);
+    }
+}
+
+fn check<G: FnMut(u64, usize, &mut Vec<u8>), R: FnMut(), C: FnMut()>(
+    gen: &mut G,
+    reference: &mut R,
+    candidate: &mut C,
+    seed: u64,
+    size: usize,
+) -> Option<Counterexample> {
+    let mut input = Vec::new();
+    gen(seed, size, &mut input);
+    let expected = capture(&input, &mut *reference);
+    let actual = capture(&input, &mut *candidate);
+    if expected == actual {
+        None
+    } else {
+        Some(Counterexample {
+            seed,
+            size,
+            input,
+            expected,
+            actual,
+        })
+    }
+}
+
+/// Run both solutions on the input generated by `gen(seed, size,
+/// sink)` for every seed in `0..seeds`, with sizes cycling through
+/// `1..=max_size`. Once a counterexample is found, the generator is
+/// run again from size 1 up, for each seed below [`SEARCH_SEEDS`],
+/// and the first counterexample of the smallest size is returned.
+/// The input itself is never shrunk, a generator which makes small
+/// inputs for small sizes gives small counterexamples.
+pub fn stress<G: FnMut(u64, usize, &mut Vec<u8>), R: FnMut(), C: FnMut()>(
+    mut gen: G,
+    mut reference: R,
+    mut candidate: C,
+    seeds: u64,
+    max_size: usize,
+) -> Result<(), Counterexample> {
+    assert!(max_size > 0, "max_size must be positive");
+    let max = u64::try_from(max_size).unwrap_or(u64::max_value());
+
+    let found = (0..seeds).find_map(|seed| {
+        let size = usize::try_from(u64::wrapping_rem(seed, max)).unwrap_or(0);
+        check(
+            &mut gen,
+            &mut reference,
+            &mut candidate,
+            seed,
+            usize::wrapping_add(size, 1),
+        )
+    });
+
+    match found {
+        None => Ok(()),
+        Some(found) => Err((1..=found.size)
+            .find_map(|size| {
+                (0..SEARCH_SEEDS)
+                    .find_map(|seed| check(&mut gen, &mut reference, &mut candidate, seed, size))
+            })
+            .unwrap_or(found)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stress;
+    use crate::fmt::{f, fwrite};
+    use crate::stdio::{read, writelnf};
+    use alloc::vec::Vec;
+
+    fn reference() {
+        let n: usize = read!();
+        let mut sum = 0;
+        for _ in 0..n {
+            let x: usize = read!();
+            sum = usize::wrapping_add(sum, x);
+        }
+        writelnf!("{:d}", sum);
+    }
+
+    // forgets the last number when there are more than three
+    fn candidate() {
+        let n: usize = read!();
+        let mut sum = 0;
+        for i in 0..n {
+            let x: usize = read!();
+            if i < 3 || i < usize::wrapping_sub(n, 1) {
+                sum = usize::wrapping_add(sum, x);
+            }
+        }
+        writelnf!("{:d}", sum);
+    }
+
+    fn gen(_seed: u64, size: usize, sink: &mut Vec<u8>) {
+        fwrite(sink, f!("{:d}\n", size));
+        for i in 0..size {
+            fwrite(sink, f!("{:d} ", usize::wrapping_add(i, 7)));
+        }
+        fwrite(sink, f!("\n"));
+    }
+
+    #[test]
+    fn test_stress() {
+        assert!(stress(gen, reference, reference, 100, 10).is_ok());
+
+        let c = stress(gen, reference, candidate, 100, 10).err().unwrap();
+        assert!(c.size == 4);
+        assert!(c.seed == 0);
+        assert!(c.input.as_slice() == b"4\n7 8 9 10 \n");
+        assert!(c.expected.as_slice() == b"34\n");
+        assert!(c.actual.as_slice() == b"24\n");
+    }
+}
@@ -0,0 +1,205 @@
+#![feature(proc_macro_hygiene)]
+
+extern crate porus;
+use porus::checker::{self, quitf, Verdict};
+use porus::libc;
+
+use std::env;
+use std::ffi::CString;
+use std::fs::{create_dir_all, remove_dir_all, write, File};
+use std::io::Read;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::FromRawFd;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::process;
+
+extern "C" {
+    fn pipe(fds: *mut i32) -> i32;
+    fn fork() -> i32;
+    fn dup2(oldfd: i32, newfd: i32) -> i32;
+    fn waitpid(pid: i32, status: *mut i32, options: i32) -> i32;
+}
+
+/// Exit code of the checker if `check` returns, or panics, without
+/// ending the check.
+const NO_VERDICT: i32 = 100;
+
+/// Run `check` as a checker of `output` against `answer` for `input`.
+/// Ending the check exits, so it is run in a child process. Returns
+/// the exit code and what is written to the standard error.
+fn run<F: FnOnce()>(
+    name: &str,
+    input: &str,
+    output: &str,
+    answer: &str,
+    check: F,
+) -> (i32, String) {
+    let dir = env::temp_dir().join(format!("porus-checker-{}-{}", process::id(), name));
+    create_dir_all(&dir).unwrap();
+    let mut args = vec![CString::new("checker").unwrap()];
+    for &(file, content) in &[("input", input), ("output", output), ("answer", answer)] {
+        let path = dir.join(file);
+        write(&path, content).unwrap();
+        args.push(CString::new(path.as_os_str().as_bytes()).unwrap());
+    }
+    let argv: Vec<*const u8> = args.iter().map(|a| a.as_ptr() as *const u8).collect();
+
+    let mut fds = [0; 2];
+    assert!(unsafe { pipe(fds.as_mut_ptr()) } == 0);
+    let pid = unsafe { fork() };
+    assert!(pid >= 0);
+    if pid == 0 {
+        unsafe {
+            libc::close(fds[0]);
+            dup2(fds[1], 2);
+            checker::set_args(argv.len() as i32, argv.as_ptr());
+        }
+        checker::initialize();
+        catch_unwind(AssertUnwindSafe(check)).ok();
+        unsafe { libc::exit(NO_VERDICT) }
+    }
+
+    unsafe { libc::close(fds[1]) };
+    let mut stderr = String::new();
+    unsafe { File::from_raw_fd(fds[0]) }
+        .read_to_string(&mut stderr)
+        .unwrap();
+    let mut status = 0;
+    assert!(unsafe { waitpid(pid, &mut status, 0) } == pid);
+    remove_dir_all(&dir).unwrap();
+
+    assert!(
+        status & 0x7f == 0,
+        "checker killed by signal {}",
+        status & 0x7f
+    );
+    ((status >> 8) & 0xff, stderr)
+}
+
+/// Compare `n` numbers given in the input, the same way as the
+/// example in `porus::checker`.
+fn compare() {
+    let n: usize = checker::input().next();
+    for _ in 0..n {
+        let expected: i64 = checker::answer().next();
+        let found: i64 = checker::output().next();
+        if expected != found {
+            quitf!(
+                Verdict::WrongAnswer,
+                "expected {:d}, found {:d}",
+                expected,
+                found
+            );
+        }
+    }
+    checker::output().skip_eof();
+    quitf!(Verdict::Accepted, "{:d} numbers", n);
+}
+
+#[test]
+fn test_verdict_codes() {
+    assert!(Verdict::Accepted.code() == 0);
+    assert!(Verdict::WrongAnswer.code() == 1);
+    assert!(Verdict::PresentationError.code() == 2);
+    assert!(Verdict::Fail.code() == 3);
+}
+
+#[test]
+fn test_accepted() {
+    let (code, stderr) = run("accepted", "2\n", " 1\n\n2 \n", "1 2\n", compare);
+    assert!(code == 0);
+    assert!(stderr == "ok 2 numbers\n");
+}
+
+#[test]
+fn test_wrong_answer() {
+    let (code, stderr) = run("wrong-answer", "2\n", "1 3\n", "1 2\n", compare);
+    assert!(code == 1);
+    assert!(stderr == "wrong answer expected 2, found 3\n");
+}
+
+#[test]
+fn test_output_ends_early() {
+    let (code, stderr) = run("ends-early", "2\n", "1\n", "1 2\n", compare);
+    assert!(code == 2);
+    assert!(stderr == "wrong output format output: unexpected end of file\n");
+}
+
+#[test]
+fn test_output_unexpected_character() {
+    let (code, stderr) = run("unexpected-character", "2\n", "1 x\n", "1 2\n", compare);
+    assert!(code == 2);
+    assert!(stderr == "wrong output format output: unexpected character\n");
+}
+
+#[test]
+fn test_output_extra_data() {
+    let (code, stderr) = run("extra-data", "2\n", "1 2 3\n", "1 2\n", compare);
+    assert!(code == 2);
+    assert!(stderr == "wrong output format output: extra data\n");
+}
+
+#[test]
+fn test_malformed_answer() {
+    let (code, stderr) = run("malformed-answer", "2\n", "1 2\n", "1 -\n", compare);
+    assert!(code == 3);
+    assert!(stderr == "FAIL answer: unexpected character\n");
+}
+
+#[test]
+fn test_ensure() {
+    let check = || checker::ensure(checker::input().next::<usize>() > 0, "n must be positive");
+    let (code, stderr) = run("ensure", "0\n", "", "", check);
+    assert!(code == 3);
+    assert!(stderr == "FAIL n must be positive\n");
+
+    let (code, stderr) = run("ensure-holds", "1\n", "", "", check);
+    assert!(code == NO_VERDICT);
+    assert!(stderr.is_empty());
+}
+
+/// Expect exactly two numbers separated by a space on a single line.
+fn strict() {
+    let output = checker::output();
+    let _: i64 = output.next();
+    output.space();
+    let _: i64 = output.next();
+    output.eoln();
+    output.eof();
+    quitf!(Verdict::Accepted, "");
+}
+
+#[test]
+fn test_strict() {
+    let (code, _) = run("strict", "", "1 2\n", "", strict);
+    assert!(code == 0);
+
+    let cases = [
+        ("strict-space", "1\n2\n", "expected space"),
+        ("strict-eoln", "1 2 \n", "expected end of line"),
+        ("strict-eof", "1 2\n\n", "expected end of file"),
+    ];
+    for &(name, output, message) in &cases {
+        let (code, stderr) = run(name, "", output, "", strict);
+        assert!(code == 2);
+        assert!(stderr == format!("wrong output format output: {}\n", message));
+    }
+}
+
+#[test]
+fn test_seek_eof() {
+    let check = || {
+        let output = checker::output();
+        let _: i64 = output.next();
+        let done = output.seek_eof();
+        quitf!(Verdict::Accepted, "{:s}", if done { "eof" } else { "more" });
+    };
+
+    let (code, stderr) = run("seek-eof", "", "1 \n\n", "", check);
+    assert!(code == 0);
+    assert!(stderr == "ok eof\n");
+
+    let (code, stderr) = run("seek-eof-more", "", "1 \n2", "", check);
+    assert!(code == 0);
+    assert!(stderr == "ok more\n");
+}
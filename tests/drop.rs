@@ -1,4 +1,6 @@
 extern crate porus;
+use porus::allocator::TrackingPool;
+use porus::prelude::*;
 
 use std::cell::Cell;
 
@@ -27,3 +29,161 @@ trait CollectionDropTest {
         assert!(counter.into_inner() == 10);
     }
 }
+
+struct TestChunk {}
+
+impl CollectionDropTest for TestChunk {
+    fn test_drop_impl<'a, It: ExactSizeIterator<Item = Item<'a>>>(it: It) {
+        let mut chunk: Chunk<Item<'a>> = Chunk::new_with_capacity(0);
+        let handles: Vec<_> = it.map(|item| pool::add(&mut chunk, item)).collect();
+        for &handle in handles.iter().step_by(3) {
+            drop(pool::remove(&mut chunk, handle));
+        }
+        assert!(chunk.len() == handles.len() - (handles.len() + 2) / 3);
+    }
+}
+
+struct TestArray {}
+
+impl CollectionDropTest for TestArray {
+    fn test_drop_impl<'a, It: ExactSizeIterator<Item = Item<'a>>>(it: It) {
+        let mut array: Array<Item<'a>> = it.collect();
+        drop(array.remove(3));
+        drop(stack::pop(&mut array));
+        array.truncate(5);
+    }
+}
+
+struct TestSmallVec {}
+
+impl CollectionDropTest for TestSmallVec {
+    fn test_drop_impl<'a, It: ExactSizeIterator<Item = Item<'a>>>(it: It) {
+        let mut v: SmallVec<Item<'a>, 4> = SmallVec::new();
+        for item in it {
+            stack::push(&mut v, item);
+        }
+        drop(stack::pop(&mut v));
+    }
+}
+
+struct TestRingBuffer {}
+
+impl CollectionDropTest for TestRingBuffer {
+    fn test_drop_impl<'a, It: ExactSizeIterator<Item = Item<'a>>>(it: It) {
+        let mut ring: RingBuffer<Item<'a>> = RingBuffer::new_with_capacity(4);
+        for (i, item) in it.enumerate() {
+            if i % 2 == 0 {
+                deque::push_front(&mut ring, item);
+            } else {
+                deque::push_back(&mut ring, item);
+            }
+        }
+        drop(deque::pop_front(&mut ring));
+        drop(deque::pop_back(&mut ring));
+    }
+}
+
+struct TestPool {}
+
+impl CollectionDropTest for TestPool {
+    fn test_drop_impl<'a, It: ExactSizeIterator<Item = Item<'a>>>(it: It) {
+        // allocator::Pool does not know its elements, every element
+        // has to be removed
+        let mut p: Pool<Item<'a>> = Pool::new();
+        let handles: Vec<_> = it.map(|item| pool::add(&mut p, item)).collect();
+        for handle in handles {
+            drop(pool::remove(&mut p, handle));
+        }
+    }
+}
+
+struct TestTrackingPool {}
+
+impl CollectionDropTest for TestTrackingPool {
+    fn test_drop_impl<'a, It: ExactSizeIterator<Item = Item<'a>>>(it: It) {
+        let mut p: TrackingPool<Item<'a>> = TrackingPool::new();
+        let handles: Vec<_> = it.map(|item| pool::add(&mut p, item)).collect();
+        drop(pool::remove(&mut p, handles[handles.len() / 2]));
+    }
+}
+
+struct TestDoublyLinkedList {}
+
+impl CollectionDropTest for TestDoublyLinkedList {
+    fn test_drop_impl<'a, It: ExactSizeIterator<Item = Item<'a>>>(it: It) {
+        let mut list: DoublyLinkedList<Item<'a>> = DoublyLinkedList::new();
+        for item in it {
+            deque::push_back(&mut list, item);
+        }
+        drop(deque::pop_front(&mut list));
+    }
+}
+
+struct TestDoublyLinkedListChunk {}
+
+impl CollectionDropTest for TestDoublyLinkedListChunk {
+    fn test_drop_impl<'a, It: ExactSizeIterator<Item = Item<'a>>>(it: It) {
+        let mut list = DoublyLinkedList::new_with_pool(Chunk::<_>::new_with_capacity(it.len()));
+        for item in it {
+            deque::push_back(&mut list, item);
+        }
+        drop(deque::pop_back(&mut list));
+    }
+}
+
+struct TestSinglyLinkedList {}
+
+impl CollectionDropTest for TestSinglyLinkedList {
+    fn test_drop_impl<'a, It: ExactSizeIterator<Item = Item<'a>>>(it: It) {
+        let mut list: SinglyLinkedList<Item<'a>> = SinglyLinkedList::new();
+        for item in it {
+            stack::push(&mut list, item);
+        }
+        drop(stack::pop(&mut list));
+    }
+}
+
+#[test]
+fn test_chunk_drop() {
+    TestChunk::test_drop();
+}
+
+#[test]
+fn test_array_drop() {
+    TestArray::test_drop();
+}
+
+#[test]
+fn test_small_vec_drop() {
+    TestSmallVec::test_drop();
+}
+
+#[test]
+fn test_ring_buffer_drop() {
+    TestRingBuffer::test_drop();
+}
+
+#[test]
+fn test_pool_drop() {
+    TestPool::test_drop();
+}
+
+#[test]
+fn test_tracking_pool_drop() {
+    TestTrackingPool::test_drop();
+}
+
+#[test]
+fn test_dlist_drop() {
+    TestDoublyLinkedList::test_drop();
+}
+
+#[test]
+fn test_dlist_chunk_drop() {
+    TestDoublyLinkedListChunk::test_drop();
+}
+
+#[test]
+fn test_flist_drop() {
+    TestSinglyLinkedList::test_drop();
+}
@@ -0,0 +1,92 @@
+extern crate porus;
+use porus::file;
+use porus::io::{Sink, TrySink, TrySource};
+use porus::libc;
+
+extern "C" {
+    fn pipe(fds: *mut i32) -> i32;
+}
+
+fn new_pipe() -> (i32, i32) {
+    let mut fds = [0; 2];
+    assert!(unsafe { pipe(fds.as_mut_ptr()) } == 0);
+    (fds[0], fds[1])
+}
+
+#[test]
+fn test_write_closed_pipe() {
+    // the test harness ignores SIGPIPE, so that writes fail with EPIPE
+    let (reader, writer) = new_pipe();
+    unsafe { libc::close(reader) };
+
+    let mut buffer = [0; 16];
+    let mut sink = file::Sink::new(writer, &mut buffer);
+    TrySink::try_write(&mut sink, 0).unwrap();
+    assert!(TrySink::try_flush(&mut sink) == Err(libc::EPIPE));
+
+    // the rest of output is discarded
+    for c in 0..100 {
+        Sink::write(&mut sink, c);
+    }
+    drop(sink);
+    unsafe { libc::close(writer) };
+}
+
+#[test]
+fn test_flush_bad_fd() {
+    let mut buffer = [0; 4];
+    let mut sink = file::Sink::new(-1, &mut buffer);
+    for c in 0..4 {
+        TrySink::try_write(&mut sink, c).unwrap();
+    }
+    assert!(TrySink::try_write(&mut sink, 4) == Err(libc::EBADF));
+
+    TrySink::try_write(&mut sink, 5).unwrap();
+    assert!(TrySink::try_flush(&mut sink) == Err(libc::EBADF));
+}
+
+#[test]
+#[should_panic(expected = "write failed: ")]
+fn test_write_bad_fd() {
+    let mut buffer = [0; 1];
+    let mut sink = file::Sink::new(-1, &mut buffer);
+    Sink::write(&mut sink, 0);
+    Sink::write(&mut sink, 1);
+}
+
+#[test]
+fn test_read_pipe() {
+    let (reader, writer) = new_pipe();
+    let mut buffer = [0; 4];
+    let mut sink = file::Sink::new(writer, &mut buffer);
+    for &c in b"hello, pipe" {
+        Sink::write(&mut sink, c);
+    }
+    drop(sink);
+    unsafe { libc::close(writer) };
+
+    let mut buffer = [0; 4];
+    let source = file::Source::new(reader, &mut buffer);
+    assert!(source.collect::<Vec<u8>>() == b"hello, pipe");
+    unsafe { libc::close(reader) };
+}
+
+#[test]
+fn test_read_bad_fd() {
+    let mut buffer = [0; 4];
+    let mut source = file::Source::new(-1, &mut buffer);
+    assert!(TrySource::try_next(&mut source) == Err(libc::EBADF));
+}
+
+#[test]
+fn test_read_error_no_stale_data() {
+    // reading a directory fails with EISDIR, every time
+    let fd = unsafe { libc::open(b"/\0".as_ptr(), libc::O_RDONLY) };
+    assert!(fd >= 0);
+
+    let mut buffer = [b'x'; 4];
+    let mut source = file::Source::new(fd, &mut buffer);
+    assert!(TrySource::try_next(&mut source) == Err(libc::EISDIR));
+    assert!(TrySource::try_next(&mut source) == Err(libc::EISDIR));
+    unsafe { libc::close(fd) };
+}
@@ -1,7 +1,15 @@
+#![feature(allocator_api)]
+
 extern crate porus;
+use porus::allocator::TrackingPool;
+use porus::capacity::DefaultPolicy;
+use porus::chunk::{GenerationalChunk, NonMaxU16, NonMaxU32};
+use porus::pool::IterablePool;
 use porus::prelude::*;
 
+use std::alloc::Global;
 use std::mem::size_of;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 
 trait PoolTest {
     type Handle: pool::Handle;
@@ -32,3 +40,138 @@ fn test_alloc_handle_non_nul() {
 fn test_chunk_handle_non_nul() {
     TestChunk::test_handle_non_null();
 }
+
+struct TestGenerationalChunk {}
+
+impl PoolTest for TestGenerationalChunk {
+    type Handle = porus::chunk::GenerationalHandle;
+}
+
+#[test]
+fn test_generational_chunk_handle_non_nul() {
+    TestGenerationalChunk::test_handle_non_null();
+}
+
+#[test]
+fn test_generational_chunk_stale_handle() {
+    let mut chunk: GenerationalChunk<usize> = GenerationalChunk::new_with_capacity(0);
+    let a = pool::add(&mut chunk, 1);
+    let b = pool::add(&mut chunk, 2);
+    assert!(pool::remove(&mut chunk, a) == 1);
+
+    let c = pool::add(&mut chunk, 3);
+    assert!(chunk.try_get(a).is_none());
+    assert!(*pool::get(&chunk, b) == 2);
+    assert!(*pool::get(&chunk, c) == 3);
+    assert!(chunk.len() == 2);
+
+    chunk.clear();
+    assert!(!chunk.is_live(b));
+    assert!(!chunk.is_live(c));
+}
+
+#[test]
+#[should_panic(expected = "stale handle")]
+fn test_generational_chunk_use_after_remove() {
+    let mut chunk: GenerationalChunk<usize> = GenerationalChunk::new_with_capacity(0);
+    let a = pool::add(&mut chunk, 1);
+    pool::remove(&mut chunk, a);
+    pool::add(&mut chunk, 2);
+    pool::get(&chunk, a);
+}
+
+struct TestTrackingPool {}
+
+impl PoolTest for TestTrackingPool {
+    type Handle = porus::allocator::Handle;
+}
+
+#[test]
+fn test_tracking_pool_handle_non_nul() {
+    TestTrackingPool::test_handle_non_null();
+}
+
+fn test_iterable<P: IterablePool<Elem = usize>>(mut p: P) {
+    let handles: Vec<_> = (0..20).map(|i| pool::add(&mut p, i)).collect();
+    for &h in handles.iter().filter(|&&h| *pool::get(&p, h) % 3 == 0) {
+        pool::remove(&mut p, h);
+    }
+
+    for x in pool::iter_mut(&mut p) {
+        *x *= 2;
+    }
+    let mut found: Vec<usize> = pool::iter(&p).cloned().collect();
+    found.sort();
+    let expected: Vec<usize> = (0..20).filter(|x| x % 3 != 0).map(|x| x * 2).collect();
+    assert!(found == expected);
+    assert!(pool::handles(&p).count() == expected.len());
+}
+
+#[test]
+fn test_chunk_iter() {
+    test_iterable(Chunk::<usize>::new_with_capacity(0));
+}
+
+#[test]
+fn test_generational_chunk_iter() {
+    test_iterable(GenerationalChunk::<usize>::new_with_capacity(0));
+}
+
+#[test]
+fn test_tracking_pool_iter() {
+    test_iterable(TrackingPool::<usize>::new());
+}
+
+struct TestChunkU32 {}
+
+impl PoolTest for TestChunkU32 {
+    type Handle = porus::chunk::Handle<NonMaxU32>;
+}
+
+struct TestChunkU16 {}
+
+impl PoolTest for TestChunkU16 {
+    type Handle = porus::chunk::Handle<NonMaxU16>;
+}
+
+#[test]
+fn test_chunk_u32_handle_non_nul() {
+    TestChunkU32::test_handle_non_null();
+    assert!(size_of::<porus::chunk::Handle<NonMaxU32>>() == 4);
+}
+
+#[test]
+fn test_chunk_u16_handle_non_nul() {
+    TestChunkU16::test_handle_non_null();
+    assert!(size_of::<porus::chunk::Handle<NonMaxU16>>() == 2);
+}
+
+type ChunkU16<T> = Chunk<T, DefaultPolicy, Global, NonMaxU16>;
+
+#[test]
+fn test_chunk_u16_iter() {
+    test_iterable(ChunkU16::<usize>::new_with_capacity(0));
+}
+
+#[test]
+#[should_panic(expected = "index overflow")]
+fn test_chunk_u16_overflow() {
+    let mut chunk = ChunkU16::<()>::new_with_capacity(0);
+    for _ in 0..=65535 {
+        pool::add(&mut chunk, ());
+    }
+}
+
+#[test]
+fn test_chunk_u16_overflow_intact() {
+    let mut chunk = ChunkU16::<usize>::new_with_capacity(0);
+    let handles: Vec<_> = (0..65535).map(|i| pool::add(&mut chunk, i)).collect();
+    let result = catch_unwind(AssertUnwindSafe(|| pool::add(&mut chunk, 65535)));
+    assert!(result.is_err());
+    assert!(chunk.len() == 65535);
+
+    assert!(pool::remove(&mut chunk, handles[100]) == 100);
+    let h = pool::add(&mut chunk, 65535);
+    assert!(*pool::get(&chunk, h) == 65535);
+    assert!(*pool::get(&chunk, handles[65534]) == 65534);
+}
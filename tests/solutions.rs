@@ -0,0 +1,151 @@
+//! Build every solution under `solutions/` which has sample input
+//! `X.in` next to it, run it and compare its output with `X.out`.
+//!
+//! porus is built with `local-judge` into `target/solutions`, so that
+//! floating numbers are compared with the precision they are printed
+//! with, the same way as `porus-judge` does. The heap of every
+//! solution is limited to `MEMORY_LIMIT`.
+
+use std::env;
+use std::fs::{create_dir_all, read, read_dir, read_to_string, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+#[path = "../porus_judge/compare.rs"]
+mod compare;
+
+/// Passed as `PORUS_MEMORY_LIMIT`, see `porus::allocator::stats`.
+const MEMORY_LIMIT: u64 = 256 << 20;
+
+/// Solutions known to fail, relative to the repository. They are still
+/// checked, and the test fails once one of them passes, so that it is
+/// removed from here.
+const KNOWN_FAILURES: &[&str] = &[];
+
+/// Same as `PRELUDE` in `c.py`.
+const PRELUDE: &str = "#![feature(proc_macro_hygiene)]
+#![feature(main)]
+#![cfg_attr(not(debug_assertions), no_std)]
+";
+
+fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn find_solutions(dir: &Path, found: &mut Vec<PathBuf>) {
+    for entry in read_dir(dir).expect("read_dir failed") {
+        let path = entry.expect("read_dir failed").path();
+        if path.is_dir() {
+            find_solutions(&path, found);
+        } else if path.extension().map_or(false, |e| e == "rs")
+            && path.with_extension("in").exists()
+        {
+            found.push(path);
+        }
+    }
+}
+
+fn build_porus(target: &Path) {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = Command::new(cargo)
+        .args(&["build", "--lib", "-q", "--features", "local-judge"])
+        .env("CARGO_TARGET_DIR", target)
+        .current_dir(root())
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "failed to build porus");
+}
+
+fn compile(solution: &Path, target: &Path, dest: &Path) -> Result<(), String> {
+    let source = read_to_string(solution).map_err(|e| e.to_string())?;
+    let deps = target.join("debug").join("deps");
+    let rlib = target.join("debug").join("libporus.rlib");
+
+    let mut child = Command::new(env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string()))
+        .args(&["--edition", "2018", "--cfg", "feature=\"local-judge\""])
+        .arg("-L")
+        .arg(format!("dependency={}", deps.display()))
+        .arg("--extern")
+        .arg(format!("porus={}", rlib.display()))
+        .arg("-o")
+        .arg(dest)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    let stdin = child.stdin.as_mut().expect("failed to open stdin");
+    stdin
+        .write_all(PRELUDE.as_bytes())
+        .and_then(|()| stdin.write_all(source.as_bytes()))
+        .map_err(|e| e.to_string())?;
+
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+}
+
+fn check(solution: &Path, target: &Path) -> Result<(), String> {
+    let relative = solution
+        .strip_prefix(root())
+        .expect("solution outside of repository");
+    let dest = target.join("bin").join(relative).with_extension("");
+    create_dir_all(dest.parent().expect("no parent")).map_err(|e| e.to_string())?;
+
+    compile(solution, target, &dest).map_err(|e| format!("CE\n{}", e))?;
+
+    let input = File::open(solution.with_extension("in")).map_err(|e| e.to_string())?;
+    let output = Command::new(&dest)
+        .env("PORUS_MEMORY_LIMIT", MEMORY_LIMIT.to_string())
+        .stdin(input)
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!("RE ({})", output.status));
+    }
+
+    let expected = read(solution.with_extension("out")).map_err(|e| e.to_string())?;
+    compare::compare(&output.stdout, &expected).map_err(|(i, actual, expected)| {
+        format!("WA (token {}: expected {}, found {})", i, expected, actual)
+    })
+}
+
+#[test]
+fn test_solutions() {
+    let target = root().join("target").join("solutions");
+    build_porus(&target);
+
+    let mut solutions = Vec::new();
+    find_solutions(&root().join("solutions"), &mut solutions);
+    solutions.sort();
+    assert!(!solutions.is_empty(), "no solution with sample input found");
+
+    let mut failures = Vec::new();
+    for solution in &solutions {
+        let known = KNOWN_FAILURES
+            .iter()
+            .any(|&known| solution == &root().join(known));
+        match check(solution, &target) {
+            Err(e) if !known => failures.push(format!("{}: {}", solution.display(), e)),
+            Ok(()) if known => failures.push(format!(
+                "{}: passes, remove it from KNOWN_FAILURES",
+                solution.display()
+            )),
+            _ => {}
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} solutions failed\n{}",
+        failures.len(),
+        solutions.len(),
+        failures.join("\n")
+    );
+}